@@ -1,21 +1,25 @@
 use std::cell::RefCell;
 use std::fs;
-use std::fs::File;
-use std::io::Write;
 use std::rc::Rc;
-use std::rc::Weak;
 
 // use log::{debug, info, trace, warn};
 use log::{debug, trace};
 
 use anyhow::Context;
-use quick_xml::reader::Reader;
 
+use crate::blend_tree::{blend_tree, regressions};
 use crate::element::{Element, ElementFlat, ElementType, ResultList, ResultType};
+#[cfg(feature = "odson")]
+use crate::export::OdsExporter;
+use crate::export::{CsvExporter, Exporter, SummaryCsvExporter};
+use crate::inputs::{create_output, STDIO};
+use crate::interner::Interner;
 use crate::multi_result_list::MultiResultList;
-use crate::rf_parser::{diff_tree, dump_csv_to_str, dump_flat, parse_inner, ParserStats};
+use crate::rf_parser::{diff_tree, dump_csv_to_str, dump_flat, parse_tree};
+use crate::snapshot::read_tree;
 
 /// Blend XML files into a multiresult list and write a CSV file
+#[allow(dead_code)]
 pub fn blend_and_save_to_csv(
     xml_files: &Vec<String>,
     csv_file: &str,
@@ -30,18 +34,48 @@ pub fn blend_and_save_to_csv(
     }
 
     let mrl = blend(&xml_data, &xml_files, max_depth)?;
+    save_to_csv(&mrl, csv_file)
+}
 
-    #[cfg(feature = "odson")]
-    let _data = mrl.export_to_ods()?;
+/// Read and parse XML files or snapshots into Element trees
+pub fn parse_files(xml_files: &[String]) -> anyhow::Result<Vec<Element>> {
+    let mut trees = Vec::new();
+    let mut names = Interner::default();
+    for xml_file in xml_files {
+        eprintln!("Parsing {}", xml_file);
+        trees.push(read_tree(xml_file, &mut names)?);
+    }
+    Ok(trees)
+}
 
-    let result = mrl.dump_to_csv_str()?;
+/// Write a multiresult list into a CSV file, `-` writes to stdout.
+/// Statistics and execution errors go into a second file ending with `_summary.csv`.
+/// The summary is left out when writing to stdout.
+pub fn save_to_csv(mrl: &MultiResultList, csv_file: &str) -> anyhow::Result<()> {
+    CsvExporter.export(mrl, &mut create_output(csv_file)?)?;
 
-    let mut buffer = File::create(csv_file)?;
-    buffer.write(result.as_bytes())?;
+    if csv_file != STDIO && !mrl.summary_rows().is_empty() {
+        let summary_file = summary_file_name(csv_file);
+        fs::write(&summary_file, SummaryCsvExporter.export_to_vec(mrl)?)
+            .context(format!("Cannot write {}", summary_file))?;
+    }
 
     Ok(())
 }
 
+/// Write a multiresult list into an ods file with the summary in a second sheet
+#[cfg(feature = "odson")]
+pub fn save_to_ods(mrl: &MultiResultList, ods_file: &str) -> anyhow::Result<()> {
+    fs::write(ods_file, OdsExporter.export_to_vec(mrl)?)
+        .context(format!("Cannot write {}", ods_file))
+}
+
+/// `stuff.csv` becomes `stuff_summary.csv`
+fn summary_file_name(csv_file: &str) -> String {
+    let stem = csv_file.strip_suffix(".csv").unwrap_or(csv_file);
+    format!("{stem}_summary.csv")
+}
+
 /// Blend XML data into a multiresult list and generate a CSV string
 pub fn blend(
     xml_data: &Vec<String>,
@@ -49,30 +83,24 @@ pub fn blend(
     max_depth: usize,
 ) -> anyhow::Result<MultiResultList> {
     let mut trees: Vec<Element> = Vec::new();
-    let mut results: Vec<ResultList> = Vec::new();
-    let mut stats: Vec<ParserStats> = Vec::new();
 
     // Parse input files
     for xml in xml_data {
-        let mut reader = Reader::from_str(&xml);
-        reader.config_mut().trim_text(true);
-
-        let depth = 0;
-        let mut root_element: Element = Element {
-            et: ElementType::Robot,
-            children: RefCell::new(Vec::new()),
-            parent: RefCell::new(Weak::new()),
-            result: ResultType::None,
-            name: String::new(),
-        };
-        let mut stat = ParserStats { max_depth: 1 };
-
-        parse_inner(&mut reader, &mut root_element, depth, &mut stat)?;
-        trees.push(root_element);
-        debug!("Maximum tree depth {}", stat.max_depth);
-        stats.push(stat);
+        trees.push(parse_tree(xml)?);
     }
 
+    blend_trees(&trees, xml_files, max_depth, false)
+}
+
+/// Blend already parsed Element trees into a multiresult list
+pub fn blend_trees(
+    trees: &[Element],
+    names: &[String],
+    max_depth: usize,
+    collapse_passing: bool,
+) -> anyhow::Result<MultiResultList> {
+    let mut results: Vec<ResultList> = Vec::new();
+
     // Dump flat contents just as reference to compare
     for tree in trees.iter() {
         let mut result = ResultList {
@@ -94,23 +122,41 @@ pub fn blend(
 
     let trees_to_diff: Vec<Option<&Element>> = trees.iter().map(|t| Some(t)).collect();
 
-    let mrl = MultiResultList::new(trees.len());
-    let header = xml_files
+    let mut mrl = blend_list(&trees_to_diff, names)?;
+    mrl.collapse_passing = collapse_passing;
+    mrl.regressions = regressions(&blend_tree(&trees_to_diff, 0));
+    diff_tree(&trees_to_diff, &mrl, 0, max_depth, true)?;
+    //println!("{:?}",mrl);
+
+    // println!("{}", mrl.dump_to_csv_str().unwrap());
+    debug!("Finalised blending");
+    Ok(mrl)
+}
+
+/// Multiresult list with a file row naming the columns and the summaries of the trees
+pub(crate) fn blend_list(
+    trees: &[Option<&Element>],
+    names: &[String],
+) -> anyhow::Result<MultiResultList> {
+    let mut mrl = MultiResultList::new(trees.len());
+    mrl.summaries = trees
+        .iter()
+        .map(|t| t.and_then(|t| t.summary.clone()))
+        .collect();
+    let header = names
         .iter()
         .map(|f| {
             Some(ElementFlat {
                 et: ElementType::File,
                 result: ResultType::None,
-                name: f.to_string(),
+                name: f.as_str().into(),
                 depth: 0,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             })
         })
         .collect();
     mrl.push(header)?;
-    diff_tree(&trees_to_diff, &mrl, 0, max_depth, true)?;
-    //println!("{:?}",mrl);
-
-    // println!("{}", mrl.dump_to_csv_str().unwrap());
-    debug!("Finalised blending");
     Ok(mrl)
 }
@@ -0,0 +1,153 @@
+use std::rc::Rc;
+
+use crate::element::{Element, ElementType, ResultType};
+use crate::rf_parser::rows_by_position;
+
+/// Alignment of the children of multiple trees into rows
+pub(crate) type Rows = fn(&[Option<&Element>]) -> Vec<Vec<Option<Rc<Element>>>>;
+
+/// One row of several result trees aligned the same way as `diff_tree` does it,
+/// but kept as a tree instead of a flat MultiResultList.
+/// When the element is not present in a result it is None.
+#[derive(Debug)]
+pub struct BlendNode {
+    pub cells: Vec<Option<Rc<Element>>>,
+    pub children: Vec<BlendNode>,
+}
+
+impl BlendNode {
+    /// The first element that is present in any of the results
+    pub fn first(&self) -> Option<&Rc<Element>> {
+        self.cells.iter().flatten().next()
+    }
+
+    pub fn name(&self) -> &str {
+        self.first().map(|e| e.name.as_ref()).unwrap_or("")
+    }
+
+    /// True when the results disagree and the latest present result failed
+    /// while an earlier one did not. Failures of known issues are no regressions.
+    pub fn is_regression(&self) -> bool {
+        let latest = self.cells.iter().flatten().next_back();
+        if latest.is_some_and(|e| e.issue.is_some()) {
+            return false;
+        }
+        let results: Vec<&ResultType> = self.cells.iter().flatten().map(|e| &e.result).collect();
+        match results.split_last() {
+            Some((ResultType::Fail, earlier)) => earlier
+                .iter()
+                .any(|r| matches!(r, ResultType::Pass | ResultType::PassOnRetry)),
+            _ => false,
+        }
+    }
+
+    /// True when any of the results has the given status
+    pub fn has_result(&self, result: &ResultType) -> bool {
+        self.cells.iter().flatten().any(|e| &e.result == result)
+    }
+}
+
+/// Align the children of multiple Element trees into a tree of BlendNodes.
+/// The alignment is positional like in `diff_tree`, see `rows_by_position`.
+pub fn blend_tree(elements: &[Option<&Element>], max_depth: usize) -> Vec<BlendNode> {
+    blend_tree_with(elements, max_depth, rows_by_position)
+}
+
+/// Align the children into a tree of BlendNodes with another alignment
+pub(crate) fn blend_tree_with(
+    elements: &[Option<&Element>],
+    max_depth: usize,
+    rows: Rows,
+) -> Vec<BlendNode> {
+    blend_tree_inner(elements, 0, max_depth, rows)
+}
+
+fn blend_tree_inner(
+    elements: &[Option<&Element>],
+    depth: usize,
+    max_depth: usize,
+    rows: Rows,
+) -> Vec<BlendNode> {
+    let mut nodes = Vec::new();
+    if depth > max_depth && max_depth != 0 {
+        return nodes;
+    }
+
+    for cells in rows(elements) {
+        let next: Vec<Option<&Element>> = cells.iter().map(|c| c.as_deref()).collect();
+        let children = blend_tree_inner(&next, depth + 1, max_depth, rows);
+        nodes.push(BlendNode { cells, children });
+    }
+    nodes
+}
+
+/// Dotted paths of the tests that regressed in the last result,
+/// failures of known issues are not counted
+pub fn regressions(nodes: &[BlendNode]) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_regressions(nodes, "", &mut found);
+    found
+}
+
+fn collect_regressions(nodes: &[BlendNode], prefix: &str, found: &mut Vec<String>) {
+    for node in nodes {
+        let Some(first) = node.first() else {
+            continue;
+        };
+        let path = match prefix.is_empty() {
+            true => node.name().to_string(),
+            false => format!("{}.{}", prefix, node.name()),
+        };
+        match first.et {
+            ElementType::Suite => collect_regressions(&node.children, &path, found),
+            ElementType::Test if node.is_regression() => found.push(path),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn element(et: ElementType, name: &str, result: ResultType) -> Element {
+        let mut e = Element::new(et, name.to_string());
+        e.result = result;
+        e
+    }
+
+    #[test]
+    fn align_two_trees() {
+        let a = element(ElementType::Robot, "", ResultType::None);
+        let b = element(ElementType::Robot, "", ResultType::None);
+        let suite_a = element(ElementType::Suite, "S", ResultType::Pass);
+        suite_a.children.borrow_mut().push(Rc::new(element(
+            ElementType::Test,
+            "T",
+            ResultType::Pass,
+        )));
+        let suite_b = element(ElementType::Suite, "S", ResultType::Fail);
+        suite_b.children.borrow_mut().push(Rc::new(element(
+            ElementType::Test,
+            "T",
+            ResultType::Fail,
+        )));
+        suite_b.children.borrow_mut().push(Rc::new(element(
+            ElementType::Test,
+            "U",
+            ResultType::Pass,
+        )));
+        a.children.borrow_mut().push(Rc::new(suite_a));
+        b.children.borrow_mut().push(Rc::new(suite_b));
+
+        let nodes = blend_tree(&[Some(&a), Some(&b)], 0);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name(), "S");
+        assert!(nodes[0].is_regression());
+        assert_eq!(nodes[0].children.len(), 2);
+        assert!(nodes[0].children[0].is_regression());
+        assert!(nodes[0].children[1].cells[0].is_none());
+        assert!(!nodes[0].children[1].is_regression());
+        assert_eq!(regressions(&nodes), vec!["S.T"]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::blend_results::blend_list;
+use crate::blend_tree::{blend_tree_with, regressions, Rows};
+use crate::element::{Element, ElementType};
+use crate::filter::{hide_setup_teardown, limit_levels, teardown_failures, Level};
+use crate::known_issues::KnownIssues;
+use crate::multi_result_list::MultiResultList;
+use crate::result_file::ResultFile;
+use crate::rf_parser::{diff_tree, flat_element, rows_by_position, same_passing_subtree};
+
+/// How the children of the blended trees are matched into rows
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Align {
+    /// The n-th child of all trees, like the blend command
+    #[default]
+    Position,
+    /// Children of the same type and name, the n-th call of a keyword is
+    /// matched with its n-th call in the other trees. Elements missing in
+    /// the first trees are appended after the matched ones.
+    Name,
+}
+
+/// Transformation of the trees before blending
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Leave out suite and test setups and teardowns
+    HideSetupTeardown,
+    /// Only teardowns that failed in any tree with their suites and tests
+    TeardownFailures,
+    /// Stop descending at an element type, see `filter::limit_levels`
+    Level {
+        level: Level,
+        keyword_depth: Option<usize>,
+    },
+    /// Annotate the tests of known issues and add an issue column
+    KnownIssues(Rc<KnownIssues>),
+}
+
+/// Blends result files into a multiresult list without reading or writing
+/// any file, e.g. `Blender::new().max_depth(3).run(&files)`
+#[derive(Debug, Clone, Default)]
+pub struct Blender {
+    max_depth: usize,
+    filters: Vec<Filter>,
+    align: Align,
+    diff_only: bool,
+    failure_column: bool,
+}
+
+impl Blender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Levels to blend, 0 for all
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Add a filter, filters are applied in the order they are added
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Do not expand rows that passed with the same subtree in all files
+    pub fn diff_only(mut self, diff_only: bool) -> Self {
+        self.diff_only = diff_only;
+        self
+    }
+
+    /// Add the failing keyword chain of failed tests to the CSV export
+    pub fn failure_column(mut self, failure_column: bool) -> Self {
+        self.failure_column = failure_column;
+        self
+    }
+
+    fn apply_filters(&self, trees: &[&Element]) -> Vec<Element> {
+        let mut filtered: Vec<Element> = Vec::new();
+        for (i, filter) in self.filters.iter().enumerate() {
+            let trees: Vec<&Element> = match i {
+                0 => trees.to_vec(),
+                _ => filtered.iter().collect(),
+            };
+            filtered = match filter {
+                Filter::HideSetupTeardown => trees.iter().map(|t| hide_setup_teardown(t)).collect(),
+                Filter::TeardownFailures => {
+                    let copies: Vec<Element> = trees.iter().map(|t| t.deep_clone()).collect();
+                    teardown_failures(&copies)
+                }
+                Filter::Level {
+                    level,
+                    keyword_depth,
+                } => trees
+                    .iter()
+                    .map(|t| limit_levels(t, *level, *keyword_depth))
+                    .collect(),
+                Filter::KnownIssues(issues) => trees.iter().map(|t| issues.annotate(t)).collect(),
+            };
+        }
+        filtered
+    }
+
+    /// Blend the files, one column for each in the given order
+    pub fn run(&self, files: &[ResultFile]) -> anyhow::Result<MultiResultList> {
+        let names: Vec<String> = files.iter().map(|f| f.name.clone()).collect();
+        let trees: Vec<&Element> = files.iter().map(|f| &f.tree).collect();
+        self.run_trees(&names, &trees)
+    }
+
+    /// Blend parsed trees with their column labels, e.g. trees that are kept between blends
+    pub fn run_trees(
+        &self,
+        names: &[String],
+        trees: &[&Element],
+    ) -> anyhow::Result<MultiResultList> {
+        let filtered = self.apply_filters(trees);
+        let trees: Vec<Option<&Element>> = match self.filters.is_empty() {
+            true => trees.iter().map(|t| Some(*t)).collect(),
+            false => filtered.iter().map(Some).collect(),
+        };
+
+        let mut mrl = blend_list(&trees, names)?;
+        mrl.collapse_passing = self.diff_only;
+        mrl.failure_column = self.failure_column;
+        mrl.issue_column = self
+            .filters
+            .iter()
+            .any(|f| matches!(f, Filter::KnownIssues(_)));
+        match self.align {
+            Align::Position => diff_tree(&trees, &mrl, 0, self.max_depth, true)?,
+            Align::Name => blend_by_name(&trees, &mrl, 0, self.max_depth)?,
+        }
+        let rows: Rows = match self.align {
+            Align::Position => rows_by_position,
+            Align::Name => rows_by_name,
+        };
+        mrl.regressions = regressions(&blend_tree_with(&trees, 0, rows));
+        Ok(mrl)
+    }
+}
+
+/// Rows of children matched by type, name and how often the name occurred before
+pub(crate) fn rows_by_name(elements: &[Option<&Element>]) -> Vec<Vec<Option<Rc<Element>>>> {
+    let mut rows: Vec<Vec<Option<Rc<Element>>>> = Vec::new();
+    let mut row_of: HashMap<(ElementType, Rc<str>, usize), usize> = HashMap::new();
+    for (column, element) in elements.iter().enumerate() {
+        let Some(element) = element else {
+            continue;
+        };
+        let mut occurrences: HashMap<(ElementType, Rc<str>), usize> = HashMap::new();
+        for child in element.children.borrow().iter() {
+            let occurrence = occurrences
+                .entry((child.et.clone(), child.name.clone()))
+                .or_default();
+            let key = (child.et.clone(), child.name.clone(), *occurrence);
+            *occurrence += 1;
+            let row = *row_of.entry(key).or_insert_with(|| {
+                rows.push(vec![None; elements.len()]);
+                rows.len() - 1
+            });
+            rows[row][column] = Some(child.clone());
+        }
+    }
+    rows
+}
+
+fn blend_by_name(
+    elements: &[Option<&Element>],
+    mrl: &MultiResultList,
+    depth: usize,
+    max_depth: usize,
+) -> anyhow::Result<()> {
+    if depth > max_depth && max_depth != 0 {
+        return Ok(());
+    }
+    for row in rows_by_name(elements) {
+        let velem: Vec<Option<&Element>> = row.iter().map(|e| e.as_deref()).collect();
+        mrl.push(
+            velem
+                .iter()
+                .map(|e| e.map(|e| flat_element(e, depth)))
+                .collect(),
+        )?;
+        if mrl.collapse_passing && same_passing_subtree(&velem) {
+            continue;
+        }
+        blend_by_name(&velem, mrl, depth + 1, max_depth)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blend_results::blend_trees;
+    use crate::element::ResultType;
+
+    fn element(et: ElementType, name: &str, result: ResultType, children: Vec<Element>) -> Element {
+        let mut element = Element::new(et, name);
+        element.result = result;
+        for child in children {
+            element.children.borrow_mut().push(Rc::new(child));
+        }
+        element
+    }
+
+    fn file(name: &str, tests: &[(&str, ResultType)]) -> ResultFile {
+        let tests = tests
+            .iter()
+            .map(|(name, result)| element(ElementType::Test, name, result.clone(), vec![]))
+            .collect();
+        let suite = element(ElementType::Suite, "Suite", ResultType::Pass, tests);
+        ResultFile {
+            name: name.to_string(),
+            tree: element(ElementType::Robot, "", ResultType::None, vec![suite]),
+        }
+    }
+
+    #[test]
+    fn blend_like_blend_trees() -> anyhow::Result<()> {
+        let names = [
+            "robot/fixtures/rf6_output.xml",
+            "robot/fixtures/rf7_output.xml",
+        ];
+        let files = names
+            .iter()
+            .map(ResultFile::from_path)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mrl = Blender::new().max_depth(2).run(&files)?;
+        let trees: Vec<Element> = files.iter().map(|f| f.tree.deep_clone()).collect();
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        let expected = blend_trees(&trees, &names, 2, false)?;
+        assert_eq!(mrl.dump_to_csv_str()?, expected.dump_to_csv_str()?);
+
+        let filtered = Blender::new()
+            .filter(Filter::HideSetupTeardown)
+            .filter(Filter::Level {
+                level: Level::Test,
+                keyword_depth: None,
+            })
+            .run(&files)?;
+        assert!(!filtered.dump_to_csv_str()?.contains("Setup"));
+        assert!(!filtered.dump_to_csv_str()?.contains("Keyword"));
+        Ok(())
+    }
+
+    #[test]
+    fn align_by_name() -> anyhow::Result<()> {
+        let files = [
+            file("a", &[("One", ResultType::Pass), ("Two", ResultType::Pass)]),
+            file(
+                "b",
+                &[("Two", ResultType::Fail), ("Three", ResultType::Pass)],
+            ),
+        ];
+        let by_position = Blender::new().run(&files)?.dump_to_csv_str()?;
+        assert!(by_position.contains("Test,One,Pass,,Test,Two,Fail,\n"));
+
+        let by_name = Blender::new().align(Align::Name).run(&files)?;
+        let expect = "Type 0,Name 0,Result 0,Owner 0,Type 1,Name 1,Result 1,Owner 1\n\
+            File,a,None,,File,b,None,\n\
+            Suite,Suite,Pass,,Suite,Suite,Pass,\n\
+            Test,One,Pass,,-,-,-,-\n\
+            Test,Two,Pass,,Test,Two,Fail,\n\
+            -,-,-,-,Test,Three,Pass,\n";
+        assert_eq!(by_name.dump_to_csv_str()?, expect);
+        assert_eq!(by_name.regressions, vec!["Suite.Two"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Color, Print, PrintStyledContent, Stylize};
+use crossterm::{cursor, execute, queue, terminal};
+
+use crate::blend_tree::BlendNode;
+use crate::element::ResultType;
+
+/// Height of the message panel below the result rows
+const DETAIL_HEIGHT: usize = 6;
+/// Width of a single result column
+const CELL_WIDTH: usize = 8;
+
+/// A row that is currently shown in the browser
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    pub path: Vec<usize>,
+    pub depth: usize,
+}
+
+/// State of the terminal browser over a tree of blended results.
+/// Nodes are addressed by the path of child indices from the top.
+pub struct Browser {
+    nodes: Vec<BlendNode>,
+    headers: Vec<String>,
+    expanded: HashSet<Vec<usize>>,
+    cursor: usize,
+    offset: usize,
+    name_filter: String,
+    status_filter: Option<ResultType>,
+    editing_filter: bool,
+}
+
+impl Browser {
+    pub fn new(nodes: Vec<BlendNode>, headers: Vec<String>) -> Self {
+        // Show the top level suites opened
+        let expanded = (0..nodes.len()).map(|i| vec![i]).collect();
+        Browser {
+            nodes,
+            headers,
+            expanded,
+            cursor: 0,
+            offset: 0,
+            name_filter: String::new(),
+            status_filter: None,
+            editing_filter: false,
+        }
+    }
+
+    fn node(&self, path: &[usize]) -> Option<&BlendNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.nodes.get(*first)?;
+        for index in rest {
+            node = node.children.get(*index)?;
+        }
+        Some(node)
+    }
+
+    fn filtering(&self) -> bool {
+        !self.name_filter.is_empty() || self.status_filter.is_some()
+    }
+
+    fn matches(&self, node: &BlendNode) -> bool {
+        let name_ok = self.name_filter.is_empty()
+            || node
+                .name()
+                .to_lowercase()
+                .contains(&self.name_filter.to_lowercase());
+        let status_ok = match &self.status_filter {
+            Some(result) => node.has_result(result),
+            None => true,
+        };
+        name_ok && status_ok
+    }
+
+    fn subtree_matches(&self, node: &BlendNode) -> bool {
+        self.matches(node) || node.children.iter().any(|c| self.subtree_matches(c))
+    }
+
+    /// Rows that are shown with the current expansion and filter state.
+    /// While filtering, all ancestors of matching rows are shown opened.
+    pub fn visible_rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut path = Vec::new();
+        self.visible_rows_inner(&self.nodes, &mut path, &mut rows);
+        rows
+    }
+
+    fn visible_rows_inner(&self, nodes: &[BlendNode], path: &mut Vec<usize>, rows: &mut Vec<Row>) {
+        for (index, node) in nodes.iter().enumerate() {
+            path.push(index);
+            if self.filtering() {
+                if self.subtree_matches(node) {
+                    rows.push(Row {
+                        path: path.clone(),
+                        depth: path.len() - 1,
+                    });
+                    self.visible_rows_inner(&node.children, path, rows);
+                }
+            } else {
+                rows.push(Row {
+                    path: path.clone(),
+                    depth: path.len() - 1,
+                });
+                if self.expanded.contains(path) {
+                    self.visible_rows_inner(&node.children, path, rows);
+                }
+            }
+            path.pop();
+        }
+    }
+
+    /// The node under the cursor
+    pub fn selected(&self) -> Option<&BlendNode> {
+        let rows = self.visible_rows();
+        rows.get(self.cursor).and_then(|r| self.node(&r.path))
+    }
+
+    fn selected_path(&self) -> Option<Vec<usize>> {
+        self.visible_rows().get(self.cursor).map(|r| r.path.clone())
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let len = self.visible_rows().len();
+        if len == 0 {
+            self.cursor = 0;
+            return;
+        }
+        let cursor = self.cursor as isize + delta;
+        self.cursor = cursor.clamp(0, len as isize - 1) as usize;
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(path) = self.selected_path() {
+            self.expanded.insert(path);
+        }
+    }
+
+    /// Collapse the selected row or jump to its parent when it is collapsed already
+    pub fn collapse(&mut self) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        if self.expanded.remove(&path) {
+            return;
+        }
+        if path.len() > 1 {
+            let parent = path[..path.len() - 1].to_vec();
+            self.expanded.remove(&parent);
+            self.select(&parent);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(path) = self.selected_path() {
+            if !self.expanded.remove(&path) {
+                self.expanded.insert(path);
+            }
+        }
+    }
+
+    fn select(&mut self, path: &[usize]) {
+        if let Some(index) = self.visible_rows().iter().position(|r| r.path == path) {
+            self.cursor = index;
+        }
+    }
+
+    /// Move to the next regression after the cursor, wrapping around at the end.
+    /// All parents of the regression are expanded.
+    pub fn next_regression(&mut self) -> bool {
+        let mut regressions = Vec::new();
+        let mut path = Vec::new();
+        collect_regressions(&self.nodes, &mut path, &mut regressions);
+
+        let current = self.selected_path().unwrap_or_default();
+        let target = regressions
+            .iter()
+            .find(|p| **p > current)
+            .or(regressions.first())
+            .cloned();
+        match target {
+            Some(target) => {
+                for len in 1..target.len() {
+                    self.expanded.insert(target[..len].to_vec());
+                }
+                self.select(&target);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_name_filter(&mut self, filter: &str) {
+        self.name_filter = filter.to_string();
+        self.cursor = 0;
+    }
+
+    /// Cycle the status filter through fail, skip, not run, pass on retry, pass and off
+    pub fn cycle_status_filter(&mut self) {
+        self.status_filter = match self.status_filter {
+            None => Some(ResultType::Fail),
+            Some(ResultType::Fail) => Some(ResultType::Skip),
+            Some(ResultType::Skip) => Some(ResultType::NotRun),
+            Some(ResultType::NotRun) => Some(ResultType::PassOnRetry),
+            Some(ResultType::PassOnRetry) => Some(ResultType::Pass),
+            _ => None,
+        };
+        self.cursor = 0;
+    }
+
+    /// Handle a key press, returns false when the browser should quit
+    pub fn handle_key(&mut self, key: KeyEvent, page: usize) -> bool {
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                KeyCode::Backspace => {
+                    let mut filter = self.name_filter.clone();
+                    filter.pop();
+                    self.set_name_filter(&filter);
+                }
+                KeyCode::Char(c) => {
+                    let filter = format!("{}{}", self.name_filter, c);
+                    self.set_name_filter(&filter);
+                }
+                _ => (),
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-(page as isize)),
+            KeyCode::PageDown => self.move_cursor(page as isize),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.move_cursor(isize::MAX / 2),
+            KeyCode::Right | KeyCode::Char('l') => self.expand(),
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(),
+            KeyCode::Enter | KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('n') => {
+                self.next_regression();
+            }
+            KeyCode::Char('s') => self.cycle_status_filter(),
+            KeyCode::Char('/') => {
+                self.editing_filter = true;
+                self.set_name_filter("");
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// Run the interactive browser until the user quits
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        let result = self.event_loop(&mut stdout);
+        execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        result
+    }
+
+    fn event_loop(&mut self, stdout: &mut io::Stdout) -> anyhow::Result<()> {
+        loop {
+            let (width, height) = terminal::size()?;
+            let page = (height as usize).saturating_sub(DETAIL_HEIGHT + 3).max(1);
+            self.draw(stdout, width as usize, page)?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key, page) {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, stdout: &mut io::Stdout, width: usize, page: usize) -> anyhow::Result<()> {
+        let rows = self.visible_rows();
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        }
+        if self.cursor >= self.offset + page {
+            self.offset = self.cursor + 1 - page;
+        }
+        let name_width = width
+            .saturating_sub(self.headers.len() * CELL_WIDTH)
+            .max(10);
+
+        queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        let status = match &self.status_filter {
+            Some(r) => r.to_string(),
+            None => "-".to_string(),
+        };
+        let help = format!(
+            "q quit  arrows move/fold  n next regression  / name: {}{}  s status: {}",
+            self.name_filter,
+            if self.editing_filter { "_" } else { "" },
+            status
+        );
+        queue!(stdout, PrintStyledContent(fit(&help, width).reverse()))?;
+
+        let mut line = format!("{:<name_width$}", "Name");
+        for (index, header) in self.headers.iter().enumerate() {
+            let label = format!("{index}:{header}");
+            // Show the end of the file name which is the distinctive part
+            let skip = label.chars().count().saturating_sub(CELL_WIDTH - 1);
+            line.push_str(&format!(
+                " {:<w$}",
+                label.chars().skip(skip).collect::<String>(),
+                w = CELL_WIDTH - 1
+            ));
+        }
+        queue!(
+            stdout,
+            cursor::MoveTo(0, 1),
+            PrintStyledContent(fit(&line, width).bold())
+        )?;
+
+        for (line_num, row) in rows.iter().enumerate().skip(self.offset).take(page) {
+            let Some(node) = self.node(&row.path) else {
+                continue;
+            };
+            let marker = if node.children.is_empty() {
+                " "
+            } else if self.filtering() || self.expanded.contains(&row.path) {
+                "-"
+            } else {
+                "+"
+            };
+            let (et, name) = node
+                .first()
+                .map(|e| (format!("{:?}", e.et), e.qualified_name()))
+                .unwrap_or_default();
+            let label = format!("{}{} {} {}", "  ".repeat(row.depth), marker, et, name);
+            let label = fit(&label, name_width);
+            let y = (line_num - self.offset + 2) as u16;
+            queue!(stdout, cursor::MoveTo(0, y))?;
+            if line_num == self.cursor {
+                queue!(
+                    stdout,
+                    PrintStyledContent(format!("{label:<name_width$}").reverse())
+                )?;
+            } else {
+                queue!(stdout, Print(format!("{label:<name_width$}")))?;
+            }
+            for cell in node.cells.iter() {
+                let (text, color) = match cell {
+                    Some(e) => (e.result.to_string(), result_color(&e.result)),
+                    None => ("-".to_string(), Color::Reset),
+                };
+                let text = format!(" {:<w$}", fit(&text, CELL_WIDTH - 1), w = CELL_WIDTH - 1);
+                queue!(stdout, PrintStyledContent(text.with(color)))?;
+            }
+        }
+
+        let detail_top = (page + 2) as u16;
+        queue!(
+            stdout,
+            cursor::MoveTo(0, detail_top),
+            PrintStyledContent("-".repeat(width).dark_grey())
+        )?;
+        if let Some(node) = self.selected() {
+            let messages: Vec<String> = node
+                .cells
+                .iter()
+                .enumerate()
+                .filter_map(|(index, cell)| {
+                    let e = cell.as_ref()?;
+                    match &e.issue {
+                        Some(issue) => Some(format!("{index}: [{issue}] {}", e.message)),
+                        None if !e.message.is_empty() => Some(format!("{index}: {}", e.message)),
+                        None => None,
+                    }
+                })
+                .collect();
+            for (line_num, message) in messages.iter().take(DETAIL_HEIGHT).enumerate() {
+                queue!(
+                    stdout,
+                    cursor::MoveTo(0, detail_top + 1 + line_num as u16),
+                    Print(fit(&message.replace('\n', " "), width))
+                )?;
+            }
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+fn collect_regressions(nodes: &[BlendNode], path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    for (index, node) in nodes.iter().enumerate() {
+        path.push(index);
+        if node.is_regression() {
+            found.push(path.clone());
+        }
+        collect_regressions(&node.children, path, found);
+        path.pop();
+    }
+}
+
+fn result_color(result: &ResultType) -> Color {
+    match result {
+        ResultType::Pass => Color::Green,
+        ResultType::PassOnRetry => Color::Yellow,
+        ResultType::Fail => Color::Red,
+        ResultType::Skip => Color::Blue,
+        ResultType::NotRun => Color::DarkGrey,
+        ResultType::None => Color::Reset,
+    }
+}
+
+/// Cut a string to the given amount of characters
+fn fit(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blend_tree::blend_tree;
+    use crate::element::{Element, ElementType};
+    use std::rc::Rc;
+
+    fn element(et: ElementType, name: &str, result: ResultType) -> Rc<Element> {
+        let mut e = Element::new(et, name.to_string());
+        e.result = result;
+        Rc::new(e)
+    }
+
+    /// Two runs of a suite with two tests, the second test regresses
+    fn browser() -> Browser {
+        let mut roots = Vec::new();
+        for second in [ResultType::Pass, ResultType::Fail] {
+            let root = Element::new(ElementType::Robot, String::new());
+            let suite = element(ElementType::Suite, "Suite", second.clone());
+            let test_a = element(ElementType::Test, "Test A", ResultType::Pass);
+            test_a.children.borrow_mut().push(element(
+                ElementType::Keyword,
+                "Log",
+                ResultType::Pass,
+            ));
+            let test_b = element(ElementType::Test, "Test B", second.clone());
+            test_b.children.borrow_mut().push(element(
+                ElementType::Keyword,
+                "Fail",
+                second.clone(),
+            ));
+            suite.children.borrow_mut().push(test_a);
+            suite.children.borrow_mut().push(test_b);
+            root.children.borrow_mut().push(suite);
+            roots.push(root);
+        }
+        let trees: Vec<Option<&Element>> = roots.iter().map(Some).collect();
+        Browser::new(
+            blend_tree(&trees, 0),
+            vec!["a".to_string(), "b".to_string()],
+        )
+    }
+
+    #[test]
+    fn expand_and_collapse() {
+        let mut b = browser();
+        assert_eq!(b.visible_rows().len(), 3);
+        b.move_cursor(1);
+        b.expand();
+        assert_eq!(b.visible_rows().len(), 4);
+        b.collapse();
+        assert_eq!(b.visible_rows().len(), 3);
+        // Collapsing a closed row moves to the parent and closes it
+        b.collapse();
+        assert_eq!(b.visible_rows().len(), 1);
+        assert_eq!(b.selected().unwrap().name(), "Suite");
+    }
+
+    #[test]
+    fn jump_to_regression() {
+        let mut b = browser();
+        assert!(b.next_regression());
+        assert_eq!(b.selected().unwrap().name(), "Test B");
+        assert!(b.next_regression());
+        assert_eq!(b.selected().unwrap().name(), "Fail");
+        assert_eq!(
+            b.visible_rows().last().unwrap(),
+            &Row {
+                path: vec![0, 1, 0],
+                depth: 2
+            }
+        );
+        // Wraps around to the suite at the top
+        assert!(b.next_regression());
+        assert_eq!(b.selected().unwrap().name(), "Suite");
+    }
+
+    #[test]
+    fn filter_rows() {
+        let mut b = browser();
+        b.set_name_filter("log");
+        let names: Vec<String> = b
+            .visible_rows()
+            .iter()
+            .map(|r| b.node(&r.path).unwrap().name().to_string())
+            .collect();
+        assert_eq!(names, vec!["Suite", "Test A", "Log"]);
+
+        b.set_name_filter("");
+        b.cycle_status_filter();
+        let names: Vec<String> = b
+            .visible_rows()
+            .iter()
+            .map(|r| b.node(&r.path).unwrap().name().to_string())
+            .collect();
+        assert_eq!(names, vec!["Suite", "Test B", "Fail"]);
+    }
+}
@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context;
+use log::{debug, warn};
+
+use crate::element::Element;
+use crate::inputs::{read_input, STDIO};
+use crate::interner::Interner;
+use crate::rf_parser::{parse_tree_with_names, PARSER_REVISION};
+use crate::snapshot::{decode_tree, encode_tree, is_snapshot, read_tree, SNAPSHOT_VERSION};
+
+/// Start of every cache entry, changes whenever the entry layout changes
+const MAGIC: &[u8; 8] = b"BRCACHE3";
+
+/// Parser revision and tree encoding of an entry, entries of any other
+/// revision or encoding are parsed again
+fn parser_version() -> String {
+    format!("{PARSER_REVISION}.{SNAPSHOT_VERSION}")
+}
+
+/// 64 bit FNV-1a, stable across builds unlike the std hasher
+pub fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// What an entry was made from
+#[derive(Debug, Clone, PartialEq)]
+struct CacheKey {
+    version: String,
+    path: String,
+    size: u64,
+    /// Modification time in nanoseconds since the epoch
+    mtime: u128,
+    hash: u64,
+}
+
+impl CacheKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&(self.version.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(self.version.as_bytes());
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.mtime.to_le_bytes());
+        bytes.extend_from_slice(&self.hash.to_le_bytes());
+        bytes.extend_from_slice(&(self.path.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(self.path.as_bytes());
+        bytes
+    }
+
+    /// Key and the encoded tree following it
+    fn from_bytes(bytes: &[u8]) -> Option<(CacheKey, &[u8])> {
+        let rest = bytes.strip_prefix(MAGIC.as_slice())?;
+        let len = u64::from_le_bytes(rest.get(0..8)?.try_into().ok()?) as usize;
+        let version = String::from_utf8(rest.get(8..8 + len)?.to_vec()).ok()?;
+        let rest = &rest[8 + len..];
+        let size = u64::from_le_bytes(rest.get(0..8)?.try_into().ok()?);
+        let mtime = u128::from_le_bytes(rest.get(8..24)?.try_into().ok()?);
+        let hash = u64::from_le_bytes(rest.get(24..32)?.try_into().ok()?);
+        let len = u64::from_le_bytes(rest.get(32..40)?.try_into().ok()?) as usize;
+        let path = String::from_utf8(rest.get(40..40 + len)?.to_vec()).ok()?;
+        let key = CacheKey {
+            version,
+            path,
+            size,
+            mtime,
+            hash,
+        };
+        Some((key, &rest[40 + len..]))
+    }
+}
+
+/// Parsed trees of output files stored in a directory
+pub struct ParseCache {
+    pub dir: PathBuf,
+    /// Names shared by all trees parsed or loaded through the cache
+    interner: RefCell<Interner>,
+}
+
+impl ParseCache {
+    pub fn new(dir: &Path) -> Self {
+        ParseCache {
+            dir: dir.to_path_buf(),
+            interner: RefCell::new(Interner::default()),
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/blend_result` or `~/.cache/blend_result`
+    pub fn default_dir() -> PathBuf {
+        match (std::env::var_os("XDG_CACHE_HOME"), std::env::var_os("HOME")) {
+            (Some(cache), _) if !cache.is_empty() => PathBuf::from(cache).join("blend_result"),
+            (_, Some(home)) => PathBuf::from(home).join(".cache").join("blend_result"),
+            _ => std::env::temp_dir().join("blend_result_cache"),
+        }
+    }
+
+    fn entry_path(&self, path: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.bin", fnv1a(path.as_bytes())))
+    }
+
+    /// Parse an output file or take its tree from the cache. The file is only
+    /// read when its size or modification time changed, then its content hash
+    /// decides whether it really changed.
+    pub fn parse(&self, xml_file: &str) -> anyhow::Result<Element> {
+        let names = &mut *self.interner.borrow_mut();
+        if xml_file == STDIO || is_snapshot(xml_file) {
+            return read_tree(xml_file, names);
+        }
+        let path = fs::canonicalize(xml_file)
+            .context(format!("File not found {}", xml_file))?
+            .to_string_lossy()
+            .to_string();
+        let meta = fs::metadata(&path)?;
+        let mtime = meta
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let entry_path = self.entry_path(&path);
+        let entry = fs::read(&entry_path).ok();
+        let cached = entry
+            .as_deref()
+            .and_then(CacheKey::from_bytes)
+            .filter(|(key, _)| key.version == parser_version() && key.path == path);
+
+        if let Some((key, data)) = &cached {
+            if key.size == meta.len() && key.mtime == mtime {
+                match decode_tree(data, names) {
+                    Ok(tree) => return Ok(tree),
+                    Err(e) => warn!("Ignoring broken cache entry of {}: {}", xml_file, e),
+                }
+            }
+        }
+
+        let xml = read_input(xml_file)?;
+        let key = CacheKey {
+            version: parser_version(),
+            path,
+            size: meta.len(),
+            mtime,
+            hash: fnv1a(xml.as_bytes()),
+        };
+        if let Some((cached_key, data)) = &cached {
+            // Touched or copied over with the same content
+            if cached_key.hash == key.hash {
+                if let Ok(tree) = decode_tree(data, names) {
+                    self.store(&key, &tree);
+                    return Ok(tree);
+                }
+            }
+        }
+        let tree = parse_tree_with_names(&xml, names)?;
+        self.store(&key, &tree);
+        Ok(tree)
+    }
+
+    /// Write an entry, a cache that cannot be written only costs time
+    fn store(&self, key: &CacheKey, tree: &Element) {
+        let mut bytes = key.to_bytes();
+        bytes.extend(encode_tree(tree));
+        let entry_path = self.entry_path(&key.path);
+        if let Err(e) = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&entry_path, bytes)) {
+            warn!("Cannot write cache {}: {}", entry_path.display(), e);
+        } else {
+            debug!("Cached {} in {}", key.path, entry_path.display());
+        }
+    }
+
+    /// Same as `parse_files` but through the cache
+    pub fn parse_files(&self, xml_files: &[String]) -> anyhow::Result<Vec<Element>> {
+        let mut trees = Vec::new();
+        for xml_file in xml_files {
+            eprintln!("Parsing {}", xml_file);
+            trees.push(self.parse(xml_file)?);
+        }
+        Ok(trees)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reuse_entries() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("blend_result_cache_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let xml_file = dir.join("output.xml");
+        fs::copy("robot/fixtures/rf7_output.xml", &xml_file)?;
+        let xml_file = xml_file.to_string_lossy().to_string();
+        let cache = ParseCache::new(&dir.join("cache"));
+
+        let parsed = cache.parse(&xml_file)?;
+        let entries: Vec<_> = fs::read_dir(&cache.dir)?.collect();
+        assert_eq!(entries.len(), 1);
+        let entry = entries[0].as_ref().unwrap().path();
+        assert_eq!(cache.parse(&xml_file)?, parsed);
+
+        // A changed file is parsed again
+        let changed = fs::read_to_string(&xml_file)?.replace("status=\"FAIL\"", "status=\"PASS\" ");
+        fs::write(&xml_file, changed)?;
+        let reparsed = cache.parse(&xml_file)?;
+        assert_ne!(reparsed, parsed);
+        assert_eq!(cache.parse(&xml_file)?, reparsed);
+
+        // An entry of another version is parsed again and replaced
+        let bytes = fs::read(&entry)?;
+        let (mut key, data) = CacheKey::from_bytes(&bytes).unwrap();
+        key.version = "0.0.0".to_string();
+        let mut old = key.to_bytes();
+        old.extend_from_slice(data);
+        fs::write(&entry, old)?;
+        assert_eq!(cache.parse(&xml_file)?, reparsed);
+        let bytes = fs::read(&entry)?;
+        assert_eq!(
+            CacheKey::from_bytes(&bytes).unwrap().0.version,
+            parser_version()
+        );
+
+        // A broken entry falls back to parsing
+        fs::write(&entry, MAGIC)?;
+        assert_eq!(cache.parse(&xml_file)?, reparsed);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use crate::element::Element;
+use crate::failures::find_failures;
+
+/// Failed tests of all inputs that share a normalised failure message
+#[derive(Debug, PartialEq)]
+pub struct Cluster {
+    /// Normalised failure message
+    pub pattern: String,
+    /// First original message of the cluster
+    pub example: String,
+    /// Input index and test path of every failure
+    pub occurrences: Vec<(usize, String)>,
+}
+
+impl Cluster {
+    pub fn count(&self) -> usize {
+        self.occurrences.len()
+    }
+
+    /// Affected tests in order of appearance
+    pub fn tests(&self) -> Vec<&str> {
+        let mut tests: Vec<&str> = Vec::new();
+        for (_, test) in self.occurrences.iter() {
+            if !tests.contains(&test.as_str()) {
+                tests.push(test);
+            }
+        }
+        tests
+    }
+
+    /// Inputs the failures occurred in
+    pub fn runs(&self) -> Vec<usize> {
+        let mut runs: Vec<usize> = self.occurrences.iter().map(|(run, _)| *run).collect();
+        runs.dedup();
+        runs
+    }
+}
+
+/// Absolute and relative paths like `/tmp/out`, `./out`, `C:\out` or
+/// `run_42/out.txt`, but not ratios like `1/2`
+fn is_path(word: &str) -> bool {
+    let separator = |c: char| c == '/' || c == '\\';
+    let bytes = word.as_bytes();
+    let rooted = word.starts_with(separator)
+        || word.starts_with("./")
+        || word.starts_with("../")
+        || word.starts_with("~/")
+        || bytes.len() > 2
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && separator(bytes[2] as char);
+    let segments: Vec<&str> = word.split(separator).filter(|s| !s.is_empty()).collect();
+    let with_extension = segments.iter().any(|s| {
+        s.split_once('.')
+            .is_some_and(|(_, ext)| ext.starts_with(|c: char| c.is_ascii_alphabetic()))
+    });
+    rooted || segments.len() >= 2 && with_extension
+}
+
+/// Hex ids like `0x7f3a`, object ids and UUIDs
+fn is_hex_id(word: &str) -> bool {
+    if let Some(hex) = word.strip_prefix("0x") {
+        return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let digits = word.chars().filter(|c| c.is_ascii_hexdigit()).count();
+    word.len() >= 8
+        && word.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && word.chars().any(|c| c.is_ascii_digit())
+        && digits >= 8
+}
+
+/// Replace the parts of a failure message that differ between occurrences
+/// of the same problem: paths, hex ids and numbers including timestamps.
+pub fn normalize_message(message: &str) -> String {
+    let mut words = Vec::new();
+    for word in message.split_whitespace() {
+        let core = word.trim_matches(|c: char| "'\"()[]{}<>,;".contains(c) || c == ':');
+        let start = word.find(core).unwrap_or(0);
+        let (prefix, suffix) = (&word[..start], &word[start + core.len()..]);
+        let core = if core.is_empty() {
+            core.to_string()
+        } else if is_path(core) {
+            "<path>".to_string()
+        } else if is_hex_id(core) {
+            "<id>".to_string()
+        } else {
+            let mut normalized = String::new();
+            let mut in_number = false;
+            for c in core.chars() {
+                if c.is_ascii_digit() {
+                    if !in_number {
+                        normalized.push('#');
+                    }
+                    in_number = true;
+                } else {
+                    normalized.push(c);
+                    in_number = false;
+                }
+            }
+            normalized
+        };
+        words.push(format!("{prefix}{core}{suffix}"));
+    }
+    words.join(" ")
+}
+
+/// Group the failed tests of all trees by normalised failure message,
+/// the largest cluster comes first.
+pub fn cluster_failures(trees: &[Element]) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for (run, tree) in trees.iter().enumerate() {
+        for failure in find_failures(tree) {
+            let pattern = normalize_message(&failure.message);
+            match clusters.iter_mut().find(|c| c.pattern == pattern) {
+                Some(cluster) => cluster.occurrences.push((run, failure.test)),
+                None => clusters.push(Cluster {
+                    pattern,
+                    example: failure.message,
+                    occurrences: vec![(run, failure.test)],
+                }),
+            }
+        }
+    }
+    // Stable sort keeps the order of appearance for clusters of the same size
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.count()));
+    clusters
+}
+
+/// Terminal report of the clusters, listing at most `max_tests` tests each
+pub fn clusters_to_terminal(clusters: &[Cluster], names: &[String], max_tests: usize) -> String {
+    let mut out = String::new();
+    for cluster in clusters.iter() {
+        let tests = cluster.tests();
+        out.push_str(&format!(
+            "{} failures in {} tests: {}\n",
+            cluster.count(),
+            tests.len(),
+            cluster.pattern
+        ));
+        out.push_str(&format!("  Example: {}\n", cluster.example));
+        let runs: Vec<&str> = cluster
+            .runs()
+            .iter()
+            .map(|r| names.get(*r).map(|n| n.as_str()).unwrap_or("?"))
+            .collect();
+        out.push_str(&format!("  Runs: {}\n", runs.join(", ")));
+        for test in tests.iter().take(max_tests) {
+            out.push_str(&format!("    {}\n", test));
+        }
+        if tests.len() > max_tests {
+            out.push_str(&format!("    ... and {} more\n", tests.len() - max_tests));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::{ElementType, ResultType};
+    use std::rc::Rc;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_message("Timeout after 30 seconds at 2024-01-31T12:00:00.123"),
+            "Timeout after # seconds at #-#-#T#:#:#.#"
+        );
+        assert_eq!(
+            normalize_message("File '/tmp/run_42/out.txt' not found"),
+            "File '<path>' not found"
+        );
+        assert_eq!(
+            normalize_message(
+                "Object <Device at 0x7f3a2b1c> id 3f2504e0-4f89-11d3-9a0c-0305e82c3301"
+            ),
+            "Object <Device at <id>> id <id>"
+        );
+        assert_eq!(
+            normalize_message("expected 1/2 but got run_42/out.txt and C:\\tmp\\x"),
+            "expected #/# but got <path> and <path>"
+        );
+        assert_eq!(
+            normalize_message("Expected 'abc' but got 'abd'"),
+            "Expected 'abc' but got 'abd'"
+        );
+    }
+
+    fn run(failures: &[(&str, &str)]) -> Element {
+        let root = Element::new(ElementType::Robot, String::new());
+        let suite = Element::new(ElementType::Suite, "Suite".to_string());
+        for (name, message) in failures {
+            let mut test = Element::new(ElementType::Test, name.to_string());
+            test.result = ResultType::Fail;
+            test.message = message.to_string();
+            suite.children.borrow_mut().push(Rc::new(test));
+        }
+        root.children.borrow_mut().push(Rc::new(suite));
+        root
+    }
+
+    #[test]
+    fn cluster_runs() {
+        let trees = vec![
+            run(&[
+                ("A", "Assertion failed"),
+                ("B", "Connection refused to 10.0.0.1:22"),
+            ]),
+            run(&[
+                ("B", "Connection refused to 10.0.0.7:22"),
+                ("C", "Connection refused to 10.0.0.9:22"),
+            ]),
+        ];
+        let clusters = cluster_failures(&trees);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].pattern, "Connection refused to #.#.#.#:#");
+        assert_eq!(clusters[0].count(), 3);
+        assert_eq!(clusters[0].tests(), vec!["Suite.B", "Suite.C"]);
+        assert_eq!(clusters[0].runs(), vec![0, 1]);
+        assert_eq!(clusters[1].example, "Assertion failed");
+
+        let names = vec!["nightly".to_string(), "weekly".to_string()];
+        let text = clusters_to_terminal(&clusters, &names, 1);
+        assert!(text.starts_with("3 failures in 2 tests: Connection refused to #.#.#.#:#\n"));
+        assert!(text.contains("  Runs: nightly, weekly\n"));
+        assert!(text.contains("    ... and 1 more\n"));
+    }
+}
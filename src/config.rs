@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::filter::Level;
+use crate::gate::GateRules;
+
+/// File name of the project configuration
+pub const CONFIG_FILE: &str = "blend_result.toml";
+
+/// Defaults for command line options, the command line takes precedence
+///
+/// ```toml
+/// depth = 3
+/// format = "html"
+/// level = "test"
+/// diff_only = true
+/// known_issues = "known_issues.toml"
+///
+/// [labels]
+/// nightly = "results/nightly.xml"
+///
+/// [gate]
+/// max_failures = 0
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Depth of blend, browse, watch and serve
+    pub depth: Option<usize>,
+    /// Trend output format
+    pub format: Option<String>,
+    /// Column header by input file, relative to the directory of the configuration file
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub hide_setup_teardown: bool,
+    #[serde(default)]
+    pub teardown_failures: bool,
+    #[serde(default)]
+    pub diff_only: bool,
+    #[serde(default)]
+    pub failures: bool,
+    #[serde(default, with = "level_name")]
+    pub level: Option<Level>,
+    pub keyword_depth: Option<usize>,
+    /// Relative to the directory of the configuration file
+    pub known_issues: Option<String>,
+    /// Rules of the gate subcommand
+    pub gate: Option<GateRules>,
+    /// Directory of the parse cache
+    pub cache_dir: Option<String>,
+}
+
+/// Level by the same name as on the command line
+mod level_name {
+    use clap::ValueEnum;
+    use serde::{Deserialize, Deserializer};
+
+    use crate::filter::Level;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Level>, D::Error> {
+        let name = String::deserialize(d)?;
+        Level::from_str(&name, true)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text =
+            fs::read_to_string(path).context(format!("File not found {}", path.display()))?;
+        let mut config = Self::from_toml(&text)
+            .context(format!("Cannot parse configuration {}", path.display()))?;
+        if let Some(dir) = path.parent() {
+            let relative = |file: &str| dir.join(file).to_string_lossy().to_string();
+            config.known_issues = config.known_issues.as_deref().map(relative);
+            for file in config.labels.values_mut() {
+                *file = relative(file);
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// The given configuration file, otherwise the nearest one found upward
+    /// from the current directory, otherwise the defaults
+    pub fn discover(path: Option<&str>) -> anyhow::Result<Self> {
+        match path {
+            Some(path) => Self::load(Path::new(path)),
+            None => match find_config(&std::env::current_dir()?) {
+                Some(path) => Self::load(&path),
+                None => Ok(Self::default()),
+            },
+        }
+    }
+}
+
+/// Search a configuration file in a directory and all of its parents
+pub fn find_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_and_find() -> anyhow::Result<()> {
+        let config = Config::from_toml(
+            r#"
+            depth = 3
+            level = "test"
+            diff_only = true
+
+            [labels]
+            nightly = "results/nightly.xml"
+
+            [gate]
+            max_failures = 0
+            "#,
+        )?;
+        assert_eq!(config.depth, Some(3));
+        assert_eq!(config.level, Some(Level::Test));
+        assert!(config.diff_only);
+        assert!(!config.failures);
+        assert_eq!(config.labels["nightly"], "results/nightly.xml");
+        assert_eq!(config.gate.unwrap().max_failures, Some(0));
+        assert!(Config::from_toml("level = \"tests\"").is_err());
+        assert!(Config::from_toml("dept = 3").is_err());
+
+        let dir = std::env::temp_dir().join("blend_result_config_test");
+        let nested = dir.join("a").join("b");
+        fs::create_dir_all(&nested)?;
+        fs::write(
+            dir.join(CONFIG_FILE),
+            "known_issues = \"issues.toml\"\n[labels]\nnightly = \"out/nightly.xml\"",
+        )?;
+        let found = find_config(&nested).unwrap();
+        assert_eq!(found, dir.join(CONFIG_FILE));
+        let config = Config::load(&found)?;
+        assert_eq!(
+            config.known_issues,
+            Some(dir.join("issues.toml").to_string_lossy().to_string())
+        );
+        assert_eq!(
+            config.labels["nightly"],
+            dir.join("out/nightly.xml").to_string_lossy()
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 use std::cell::RefCell;
 use std::fmt;
 use std::rc::{Rc, Weak};
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Clone)]
+use anyhow::anyhow;
+
+use crate::known_issues::KnownIssue;
+use crate::summary::RunSummary;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum ElementType {
     File,
     Robot,
@@ -16,6 +22,48 @@ pub enum ElementType {
     Iter,
     While,
     Continue,
+    /// GROUP of RF 7.2
+    Group,
+    Return,
+    Break,
+    /// VAR statement of RF 7
+    Var,
+    /// Invalid syntax reported by RF 7
+    Error,
+    /// Suite or test setup keyword
+    Setup,
+    /// Suite or test teardown keyword
+    Teardown,
+}
+
+/// Parse the Debug representation back into an ElementType
+impl FromStr for ElementType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "File" => Ok(ElementType::File),
+            "Robot" => Ok(ElementType::Robot),
+            "Suite" => Ok(ElementType::Suite),
+            "Test" => Ok(ElementType::Test),
+            "Keyword" => Ok(ElementType::Keyword),
+            "If" => Ok(ElementType::If),
+            "Branch" => Ok(ElementType::Branch),
+            "Try" => Ok(ElementType::Try),
+            "For" => Ok(ElementType::For),
+            "Iter" => Ok(ElementType::Iter),
+            "While" => Ok(ElementType::While),
+            "Continue" => Ok(ElementType::Continue),
+            "Group" => Ok(ElementType::Group),
+            "Return" => Ok(ElementType::Return),
+            "Break" => Ok(ElementType::Break),
+            "Var" => Ok(ElementType::Var),
+            "Error" => Ok(ElementType::Error),
+            "Setup" => Ok(ElementType::Setup),
+            "Teardown" => Ok(ElementType::Teardown),
+            s => Err(anyhow!("Unknown element type {s}")),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -25,6 +73,8 @@ pub enum ResultType {
     NotRun,
     None,
     Skip,
+    /// Failed first and passed in a rerun of the same logical run
+    PassOnRetry,
 }
 
 impl fmt::Display for ResultType {
@@ -33,6 +83,23 @@ impl fmt::Display for ResultType {
     }
 }
 
+/// Parse the Debug representation back into a ResultType
+impl FromStr for ResultType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pass" => Ok(ResultType::Pass),
+            "Fail" => Ok(ResultType::Fail),
+            "NotRun" => Ok(ResultType::NotRun),
+            "None" => Ok(ResultType::None),
+            "Skip" => Ok(ResultType::Skip),
+            "PassOnRetry" => Ok(ResultType::PassOnRetry),
+            s => Err(anyhow!("Unknown result type {s}")),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Element {
     pub et: ElementType,
@@ -41,7 +108,69 @@ pub struct Element {
     pub children: RefCell<Vec<Rc<Element>>>,
     pub parent: RefCell<Weak<Element>>,
     pub result: ResultType,
-    pub name: String,
+    /// Shared with all elements of the same name, see `Interner`
+    pub name: Rc<str>,
+    /// Failure or skip message from the status element
+    pub message: String,
+    /// Execution time in seconds
+    pub elapsed: Option<f64>,
+    /// Library or resource of a keyword, `owner` since RF 7 and `library` before
+    pub owner: Rc<str>,
+    /// Statistics and execution errors, only set on the root of a parsed file
+    pub summary: Option<Rc<RunSummary>>,
+    /// Known issue the result of a test is expected from
+    pub issue: Option<Rc<KnownIssue>>,
+}
+
+impl Element {
+    pub fn new(et: ElementType, name: impl Into<Rc<str>>) -> Self {
+        Element {
+            et,
+            children: RefCell::new(Vec::new()),
+            parent: RefCell::new(Weak::new()),
+            result: ResultType::None,
+            name: name.into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
+        }
+    }
+
+    /// Keyword name prefixed by its owner like `BuiltIn.Log`
+    pub fn qualified_name(&self) -> String {
+        if self.owner.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{}.{}", self.owner, self.name)
+        }
+    }
+}
+
+impl Element {
+    /// Copy the element and all of its children, the parent is not copied
+    pub fn deep_clone(&self) -> Element {
+        let element = Element {
+            et: self.et.clone(),
+            children: RefCell::new(Vec::new()),
+            parent: RefCell::new(Weak::new()),
+            result: self.result.clone(),
+            name: self.name.clone(),
+            message: self.message.clone(),
+            elapsed: self.elapsed,
+            owner: self.owner.clone(),
+            summary: self.summary.clone(),
+            issue: self.issue.clone(),
+        };
+        for child in self.children.borrow().iter() {
+            element
+                .children
+                .borrow_mut()
+                .push(Rc::new(child.deep_clone()));
+        }
+        element
+    }
 }
 
 impl PartialEq for Element {
@@ -54,8 +183,13 @@ impl PartialEq for Element {
 pub struct ElementFlat {
     pub et: ElementType,
     pub result: ResultType,
-    pub name: String,
+    pub name: Rc<str>,
     pub depth: usize,
+    pub owner: Rc<str>,
+    /// Failing keyword chain and message of a failed test
+    pub failure: String,
+    /// Id and state of a known issue
+    pub issue: String,
 }
 
 #[derive(Debug)]
@@ -87,7 +221,12 @@ mod test {
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::None,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         };
         //assert
     }
@@ -99,14 +238,24 @@ mod test {
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::None,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         };
         let new_test = Element {
             et: ElementType::Test,
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::Pass,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         };
         suite.children.borrow_mut().push(Rc::new(new_test));
         let new_test2 = Element {
@@ -114,7 +263,12 @@ mod test {
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::Fail,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         };
         suite.children.borrow_mut().push(Rc::new(new_test2));
         let new_kw = Element {
@@ -122,7 +276,12 @@ mod test {
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::None,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         };
         {
             // Now we add the kw to the second test
@@ -185,14 +344,24 @@ mod test {
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::None,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         });
         let test = Rc::new(Element {
             et: ElementType::Test,
             children: RefCell::new(Vec::new()),
             parent: RefCell::new(Weak::new()),
             result: ResultType::None,
-            name: String::new(),
+            name: "".into(),
+            message: String::new(),
+            elapsed: None,
+            owner: "".into(),
+            summary: None,
+            issue: None,
         });
 
         let mut parent = kw.parent.borrow_mut();
@@ -0,0 +1,113 @@
+use std::io::Write;
+
+use serde_json::{json, Value};
+
+use crate::element::ElementFlat;
+use crate::multi_result_list::MultiResultList;
+
+/// Serialisation of a blend into any writer, e.g. a buffer or a socket
+pub trait Exporter {
+    fn export(&self, mrl: &MultiResultList, writer: &mut dyn Write) -> anyhow::Result<()>;
+
+    fn export_to_vec(&self, mrl: &MultiResultList) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.export(mrl, &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Blend table as written by the blend command
+pub struct CsvExporter;
+
+impl Exporter for CsvExporter {
+    fn export(&self, mrl: &MultiResultList, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(mrl.dump_to_csv_str()?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Metadata, statistics and execution errors of all inputs
+pub struct SummaryCsvExporter;
+
+impl Exporter for SummaryCsvExporter {
+    fn export(&self, mrl: &MultiResultList, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(mrl.dump_summary_to_csv_str()?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Column names and rows like the `/api/blend` endpoint of the serve command
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn export(&self, mrl: &MultiResultList, writer: &mut dyn Write) -> anyhow::Result<()> {
+        serde_json::to_writer(writer, &blend_json(mrl))?;
+        Ok(())
+    }
+}
+
+/// Spreadsheet with a results and a summary sheet
+#[cfg(feature = "odson")]
+pub struct OdsExporter;
+
+#[cfg(feature = "odson")]
+impl Exporter for OdsExporter {
+    fn export(&self, mrl: &MultiResultList, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(&mrl.export_to_ods()?)?;
+        Ok(())
+    }
+}
+
+fn cell_json(cell: &Option<ElementFlat>) -> Value {
+    match cell {
+        Some(e) => json!({
+            "type": format!("{:?}", e.et),
+            "name": &*e.name,
+            "result": e.result.to_string(),
+            "owner": &*e.owner,
+            "depth": e.depth,
+            "failure": e.failure,
+            "issue": e.issue,
+        }),
+        None => Value::Null,
+    }
+}
+
+/// Columns named by the file row and all further rows as arrays of cells
+fn blend_json(mrl: &MultiResultList) -> Value {
+    let list = mrl.list.borrow();
+    let columns: Vec<&str> = match list.first() {
+        Some(header) => header
+            .iter()
+            .map(|c| c.as_ref().map(|c| &*c.name).unwrap_or_default())
+            .collect(),
+        None => Vec::new(),
+    };
+    let rows: Vec<Value> = list
+        .iter()
+        .skip(1)
+        .map(|row| Value::Array(row.iter().map(cell_json).collect()))
+        .collect();
+    json!({ "columns": columns, "rows": rows })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blend_results::blend_trees;
+    use crate::rf_parser::parse_tree;
+
+    #[test]
+    fn export_without_files() -> anyhow::Result<()> {
+        let tree = parse_tree(&std::fs::read_to_string("robot/fixtures/rf7_output.xml")?)?;
+        let mrl = blend_trees(&[tree], &["rf7".to_string()], 1, false)?;
+        let csv = CsvExporter.export_to_vec(&mrl)?;
+        assert!(csv.starts_with(b"Type 0,Name 0,Result 0,Owner 0\nFile,rf7,None,\n"));
+        assert!(String::from_utf8(SummaryCsvExporter.export_to_vec(&mrl)?)?.contains("7.2"));
+
+        let json: Value = serde_json::from_slice(&JsonExporter.export_to_vec(&mrl)?)?;
+        assert_eq!(json["columns"], json!(["rf7"]));
+        assert_eq!(json["rows"][0][0]["type"], "Suite");
+        Ok(())
+    }
+}
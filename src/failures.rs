@@ -0,0 +1,222 @@
+use csv::Writer;
+
+use crate::element::{Element, ElementType, ResultType};
+
+/// The deepest chain of failing keywords of a failed test
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// Suite path and test name joined by dots
+    pub test: String,
+    /// Failing elements from the test down to the failing leaf
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+impl Failure {
+    /// Compact path like `Keyword C > Keyword B > Fail`
+    pub fn chain(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.chain(), self.message)
+        }
+    }
+}
+
+fn label(element: &Element) -> String {
+    if element.name.is_empty() {
+        format!("{:?}", element.et)
+    } else {
+        element.name.to_string()
+    }
+}
+
+/// Child that caused the failure. Execution stops at a failure so this is the
+/// last failing child, a failing teardown only counts when nothing else failed.
+fn failing_child(element: &Element) -> Option<std::rc::Rc<Element>> {
+    let children = element.children.borrow();
+    let failing = || children.iter().filter(|c| c.result == ResultType::Fail);
+    failing()
+        .rfind(|c| c.et != ElementType::Teardown)
+        .or_else(|| failing().next_back())
+        .cloned()
+}
+
+/// Follow the failing children of a failed test down to the failing leaf.
+/// The message is the one of the deepest element that has one.
+pub fn failure_of(test: &Element, test_path: &str) -> Failure {
+    let mut path = Vec::new();
+    let mut message = test.message.clone();
+    let mut current = failing_child(test);
+    while let Some(element) = current {
+        path.push(label(&element));
+        if !element.message.is_empty() {
+            message = element.message.clone();
+        }
+        current = failing_child(&element);
+    }
+    Failure {
+        test: test_path.to_string(),
+        path,
+        message,
+    }
+}
+
+fn collect_failures(element: &Element, prefix: &str, found: &mut Vec<Failure>) {
+    for child in element.children.borrow().iter() {
+        let path = if prefix.is_empty() {
+            child.name.to_string()
+        } else {
+            format!("{}.{}", prefix, child.name)
+        };
+        match child.et {
+            ElementType::Suite => collect_failures(child, &path, found),
+            ElementType::Test if child.result == ResultType::Fail => {
+                found.push(failure_of(child, &path))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Failure paths of all failed tests in a tree
+pub fn find_failures(tree: &Element) -> Vec<Failure> {
+    let mut found = Vec::new();
+    collect_failures(tree, "", &mut found);
+    found
+}
+
+/// Failures of each input with a count line per input, for the terminal
+pub fn failures_to_terminal(failures: &[Vec<Failure>], names: &[String]) -> String {
+    let mut out = String::new();
+    for (failures, name) in failures.iter().zip(names.iter()) {
+        out.push_str(&format!("{} failed tests in {}\n", failures.len(), name));
+        for failure in failures.iter() {
+            out.push_str(&format!("  {}\n    {}\n", failure.test, failure));
+        }
+    }
+    out
+}
+
+/// Failures of each input as CSV with the input name in the first column
+pub fn failures_to_csv(failures: &[Vec<Failure>], names: &[String]) -> anyhow::Result<String> {
+    let mut wtr = Writer::from_writer(vec![]);
+    wtr.write_record(["File", "Test", "Path", "Message"])?;
+    for (failures, name) in failures.iter().zip(names.iter()) {
+        for failure in failures.iter() {
+            wtr.write_record([
+                name.as_str(),
+                &failure.test,
+                &failure.chain(),
+                &failure.message,
+            ])?;
+        }
+    }
+    Ok(String::from_utf8(wtr.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rf_parser::parse_tree;
+    use std::rc::Rc;
+
+    fn element(et: ElementType, name: &str, result: ResultType, message: &str) -> Rc<Element> {
+        let mut element = Element::new(et, name.to_string());
+        element.result = result;
+        element.message = message.to_string();
+        Rc::new(element)
+    }
+
+    fn push(parent: &Rc<Element>, child: &Rc<Element>) {
+        parent.children.borrow_mut().push(child.clone());
+    }
+
+    #[test]
+    fn deepest_failing_chain() {
+        let root = Rc::new(Element::new(ElementType::Robot, String::new()));
+        let suite = element(ElementType::Suite, "Suite", ResultType::Fail, "");
+        let test = element(ElementType::Test, "Test", ResultType::Fail, "Boom");
+        let outer = element(ElementType::Keyword, "Outer", ResultType::Fail, "");
+        let branch = element(ElementType::Branch, "", ResultType::Fail, "");
+        let fail = element(ElementType::Keyword, "Fail", ResultType::Fail, "Boom");
+        let teardown = element(ElementType::Teardown, "Close", ResultType::Fail, "Gone");
+        push(&root, &suite);
+        push(&suite, &test);
+        push(
+            &suite,
+            &element(ElementType::Test, "Other", ResultType::Pass, ""),
+        );
+        push(
+            &test,
+            &element(ElementType::Keyword, "Log", ResultType::Pass, ""),
+        );
+        push(&test, &outer);
+        push(&test, &teardown);
+        push(&outer, &branch);
+        push(&branch, &fail);
+        push(
+            &fail,
+            &element(ElementType::Keyword, "Log", ResultType::NotRun, ""),
+        );
+
+        let failures = find_failures(&root);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].test, "Suite.Test");
+        assert_eq!(failures[0].chain(), "Outer > Branch > Fail");
+        assert_eq!(failures[0].to_string(), "Outer > Branch > Fail: Boom");
+
+        // Only the teardown failed
+        let test = element(ElementType::Test, "Test", ResultType::Fail, "");
+        push(
+            &test,
+            &element(ElementType::Keyword, "Log", ResultType::Pass, ""),
+        );
+        push(&test, &teardown);
+        let failure = failure_of(&test, "Test");
+        assert_eq!(failure.chain(), "Close");
+        assert_eq!(failure.message, "Gone");
+
+        let test = element(ElementType::Test, "Test", ResultType::Fail, "Empty");
+        assert_eq!(failure_of(&test, "Test").to_string(), "Empty");
+    }
+
+    #[test]
+    fn message_of_fail_msg() -> anyhow::Result<()> {
+        let xml = r#"<robot generated="20240101 00:00:00.000">
+<suite id="s1" name="Suite">
+<test id="s1-t1" name="Test">
+<kw name="Outer">
+<kw name="Fail" library="BuiltIn">
+<msg timestamp="20240101 00:00:00.001" level="INFO">Not this one</msg>
+<msg timestamp="20240101 00:00:00.001" level="FAIL">Boom</msg>
+<status status="FAIL"/>
+</kw>
+<status status="FAIL"/>
+</kw>
+<status status="FAIL"/>
+</test>
+<status status="FAIL"/>
+</suite>
+</robot>"#;
+        let failures = find_failures(&parse_tree(xml)?);
+        assert_eq!(failures[0].to_string(), "Outer > Fail: Boom");
+
+        let names = vec!["a.xml".to_string()];
+        assert_eq!(
+            failures_to_terminal(std::slice::from_ref(&failures), &names),
+            "1 failed tests in a.xml\n  Suite.Test\n    Outer > Fail: Boom\n"
+        );
+        assert_eq!(
+            failures_to_csv(&[failures], &names)?,
+            "File,Test,Path,Message\na.xml,Suite.Test,Outer > Fail,Boom\n"
+        );
+        Ok(())
+    }
+}
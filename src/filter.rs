@@ -0,0 +1,270 @@
+use std::rc::Rc;
+
+use crate::element::{Element, ElementType, ResultType};
+
+/// Copy an element without its children
+pub(crate) fn copy_element(element: &Element) -> Element {
+    let mut copy = Element::new(element.et.clone(), element.name.clone());
+    copy.result = element.result.clone();
+    copy.message = element.message.clone();
+    copy.elapsed = element.elapsed;
+    copy.owner = element.owner.clone();
+    copy.summary = element.summary.clone();
+    copy.issue = element.issue.clone();
+    copy
+}
+
+/// Remove all suite and test setups and teardowns from a tree
+pub fn hide_setup_teardown(tree: &Element) -> Element {
+    let copy = copy_element(tree);
+    for child in tree.children.borrow().iter() {
+        if !matches!(child.et, ElementType::Setup | ElementType::Teardown) {
+            copy.children
+                .borrow_mut()
+                .push(Rc::new(hide_setup_teardown(child)));
+        }
+    }
+    copy
+}
+
+/// Element type at which blending stops descending
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Level {
+    /// Only suites
+    Suite,
+    /// Suites and tests
+    Test,
+    /// Suites, tests and their top level keywords
+    Keyword,
+    /// Everything
+    All,
+}
+
+/// Cut a tree at an element type regardless of how deep suites are nested.
+/// The keyword depth counts the levels below a test or below a suite for
+/// suite setups and teardowns, control structures count as a level.
+pub fn limit_levels(tree: &Element, level: Level, keyword_depth: Option<usize>) -> Element {
+    let max_keyword_depth = match level {
+        Level::Suite | Level::Test => 0,
+        Level::Keyword => keyword_depth.unwrap_or(1),
+        Level::All => keyword_depth.unwrap_or(usize::MAX),
+    };
+    limit_levels_inner(tree, level, max_keyword_depth, 0)
+}
+
+fn limit_levels_inner(
+    element: &Element,
+    level: Level,
+    max_keyword_depth: usize,
+    keyword_depth: usize,
+) -> Element {
+    let copy = copy_element(element);
+    for child in element.children.borrow().iter() {
+        let child_depth = match child.et {
+            ElementType::Suite => Some(0),
+            ElementType::Test if level != Level::Suite => Some(0),
+            ElementType::Test => None,
+            _ if keyword_depth < max_keyword_depth => Some(keyword_depth + 1),
+            _ => None,
+        };
+        if let Some(child_depth) = child_depth {
+            copy.children.borrow_mut().push(Rc::new(limit_levels_inner(
+                child,
+                level,
+                max_keyword_depth,
+                child_depth,
+            )));
+        }
+    }
+    copy
+}
+
+/// Suites and tests from the root down to an element
+type Path = Vec<(ElementType, String)>;
+
+fn failed_teardowns(element: &Element, path: &mut Path, found: &mut Vec<Path>) {
+    for child in element.children.borrow().iter() {
+        path.push((child.et.clone(), child.name.to_string()));
+        match child.et {
+            ElementType::Suite | ElementType::Test => failed_teardowns(child, path, found),
+            ElementType::Teardown if child.result == ResultType::Fail && !found.contains(path) => {
+                found.push(path.clone())
+            }
+            _ => (),
+        }
+        path.pop();
+    }
+}
+
+fn keep_teardowns(element: &Element, path: &mut Path, keep: &[Path]) -> Option<Element> {
+    let copy = copy_element(element);
+    for child in element.children.borrow().iter() {
+        path.push((child.et.clone(), child.name.to_string()));
+        let kept = match child.et {
+            ElementType::Suite | ElementType::Test => keep_teardowns(child, path, keep),
+            ElementType::Teardown if keep.contains(path) => Some(child.deep_clone()),
+            _ => None,
+        };
+        if let Some(kept) = kept {
+            copy.children.borrow_mut().push(Rc::new(kept));
+        }
+        path.pop();
+    }
+    if copy.children.borrow().is_empty() {
+        None
+    } else {
+        Some(copy)
+    }
+}
+
+/// Reduce trees to the teardowns that failed in any of them, together with
+/// their suites and tests. A teardown is kept in all trees as soon as it
+/// failed in one so the rows stay aligned.
+pub fn teardown_failures(trees: &[Element]) -> Vec<Element> {
+    let mut failed = Vec::new();
+    for tree in trees.iter() {
+        failed_teardowns(tree, &mut Vec::new(), &mut failed);
+    }
+    trees
+        .iter()
+        .map(|tree| {
+            keep_teardowns(tree, &mut Vec::new(), &failed).unwrap_or_else(|| copy_element(tree))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn element(et: ElementType, name: &str, result: ResultType) -> Element {
+        let mut element = Element::new(et, name.to_string());
+        element.result = result;
+        element
+    }
+
+    fn push(parent: &Element, child: Element) {
+        parent.children.borrow_mut().push(Rc::new(child));
+    }
+
+    /// Suite with a passing test and a test whose teardown has the given result
+    fn run(teardown: ResultType) -> Element {
+        let root = Element::new(ElementType::Robot, String::new());
+        let suite = element(ElementType::Suite, "Suite", ResultType::Pass);
+        push(
+            &suite,
+            element(ElementType::Setup, "Open", ResultType::Pass),
+        );
+
+        let a = element(ElementType::Test, "A", ResultType::Pass);
+        push(&a, element(ElementType::Keyword, "Log", ResultType::Pass));
+        push(
+            &a,
+            element(ElementType::Teardown, "Close", ResultType::Pass),
+        );
+        push(&suite, a);
+
+        let b = element(ElementType::Test, "B", teardown.clone());
+        push(&b, element(ElementType::Keyword, "Log", ResultType::Pass));
+        let close = element(ElementType::Teardown, "Close", teardown);
+        push(
+            &close,
+            element(ElementType::Keyword, "Fail", ResultType::Fail),
+        );
+        push(&b, close);
+        push(&suite, b);
+
+        push(&root, suite);
+        root
+    }
+
+    fn rows(element: &Element, depth: usize, found: &mut Vec<String>) {
+        for child in element.children.borrow().iter() {
+            found.push(format!(
+                "{}{:?} {}",
+                " ".repeat(depth),
+                child.et,
+                child.name
+            ));
+            rows(child, depth + 1, found);
+        }
+    }
+
+    fn flat(element: &Element) -> Vec<String> {
+        let mut found = Vec::new();
+        rows(element, 0, &mut found);
+        found
+    }
+
+    #[test]
+    fn hide_fixtures() {
+        let tree = hide_setup_teardown(&run(ResultType::Fail));
+        assert_eq!(
+            flat(&tree),
+            vec![
+                "Suite Suite",
+                " Test A",
+                "  Keyword Log",
+                " Test B",
+                "  Keyword Log"
+            ]
+        );
+    }
+
+    #[test]
+    fn limit_to_level() {
+        // Nested suite next to a shallow test
+        let root = run(ResultType::Pass);
+        let suite = root.children.borrow()[0].clone();
+        let nested = element(ElementType::Suite, "Nested", ResultType::Pass);
+        let deep = element(ElementType::Test, "Deep", ResultType::Pass);
+        push(
+            &deep,
+            element(ElementType::Keyword, "Log", ResultType::Pass),
+        );
+        push(&nested, deep);
+        push(&suite, nested);
+
+        let tree = limit_levels(&root, Level::Suite, None);
+        assert_eq!(flat(&tree), vec!["Suite Suite", " Suite Nested"]);
+
+        let tree = limit_levels(&root, Level::Test, None);
+        assert_eq!(
+            flat(&tree),
+            vec![
+                "Suite Suite",
+                " Test A",
+                " Test B",
+                " Suite Nested",
+                "  Test Deep"
+            ]
+        );
+
+        let tree = limit_levels(&root, Level::Keyword, None);
+        assert_eq!(flat(&tree).len(), 11);
+        assert!(!flat(&tree).contains(&"   Keyword Fail".to_string()));
+        let tree = limit_levels(&root, Level::Keyword, Some(2));
+        assert!(flat(&tree).contains(&"   Keyword Fail".to_string()));
+        assert_eq!(limit_levels(&root, Level::All, None), root);
+    }
+
+    #[test]
+    fn only_failed_teardowns() {
+        let trees = teardown_failures(&[run(ResultType::Pass), run(ResultType::Fail)]);
+        let expect = vec![
+            "Suite Suite",
+            " Test B",
+            "  Teardown Close",
+            "   Keyword Fail",
+        ];
+        assert_eq!(flat(&trees[0]), expect);
+        assert_eq!(flat(&trees[1]), expect);
+        assert_eq!(
+            trees[0].children.borrow()[0].children.borrow()[0].result,
+            ResultType::Pass
+        );
+
+        let trees = teardown_failures(&[run(ResultType::Pass)]);
+        assert!(trees[0].children.borrow().is_empty());
+    }
+}
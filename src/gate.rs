@@ -0,0 +1,363 @@
+use std::fs;
+
+use anyhow::{anyhow, Context};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::element::{Element, ElementType, ResultType};
+
+/// Minimum pass rate of the tests with a tag
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRate {
+    pub tag: String,
+    /// Passed tests of all passed and failed ones in percent
+    pub percent: f64,
+}
+
+/// Rules of a quality gate, unset rules are not checked
+///
+/// ```toml
+/// max_failures = 0
+/// no_new_failures = true
+/// max_duration_growth = 20.0
+/// no_suite_errors = true
+///
+/// [[min_pass_rate]]
+/// tag = "smoke"
+/// percent = 100.0
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GateRules {
+    /// Failed tests allowed, known issues are not counted
+    pub max_failures: Option<usize>,
+    #[serde(default)]
+    pub min_pass_rate: Vec<TagRate>,
+    /// No test may fail that did not fail in the baseline
+    #[serde(default)]
+    pub no_new_failures: bool,
+    /// Allowed growth of the total execution time versus the baseline in percent
+    pub max_duration_growth: Option<f64>,
+    /// No failed suite setups or teardowns and no execution errors
+    #[serde(default)]
+    pub no_suite_errors: bool,
+}
+
+impl GateRules {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path).context(format!("File not found {}", path))?;
+        Self::from_toml(&text).context(format!("Cannot parse gate rules {}", path))
+    }
+
+    pub fn from_toml(text: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+/// Outcome of a single rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub rule: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(rule: String, passed: bool, detail: String) -> Self {
+        Check {
+            rule,
+            passed,
+            detail,
+        }
+    }
+}
+
+fn collect_failed_tests(element: &Element, prefix: &str, found: &mut Vec<String>) {
+    for child in element.children.borrow().iter() {
+        let path = if prefix.is_empty() {
+            child.name.to_string()
+        } else {
+            format!("{}.{}", prefix, child.name)
+        };
+        match child.et {
+            ElementType::Suite => collect_failed_tests(child, &path, found),
+            ElementType::Test if child.result == ResultType::Fail && child.issue.is_none() => {
+                found.push(path)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Paths of the failed tests that are not annotated with a known issue
+pub fn failed_tests(tree: &Element) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_failed_tests(tree, "", &mut found);
+    found
+}
+
+/// Execution time of all top level suites
+pub fn run_duration(tree: &Element) -> Option<f64> {
+    let durations: Vec<f64> = tree
+        .children
+        .borrow()
+        .iter()
+        .filter_map(|c| c.elapsed)
+        .collect();
+    (!durations.is_empty()).then(|| durations.iter().sum())
+}
+
+fn collect_suite_errors(element: &Element, found: &mut Vec<String>) {
+    for child in element.children.borrow().iter() {
+        match child.et {
+            ElementType::Suite => collect_suite_errors(child, found),
+            ElementType::Setup | ElementType::Teardown
+                if element.et == ElementType::Suite && child.result == ResultType::Fail =>
+            {
+                found.push(format!("{:?} of suite {} failed", child.et, element.name))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Failed suite setups and teardowns and ERROR messages of the errors section
+pub fn suite_errors(tree: &Element) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_suite_errors(tree, &mut found);
+    if let Some(summary) = &tree.summary {
+        for error in summary.errors.iter().filter(|e| e.level == "ERROR") {
+            found.push(error.text.clone());
+        }
+    }
+    found
+}
+
+/// Short list of names for the detail of a check
+fn listing(names: &[String]) -> String {
+    let mut text = names.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+    if names.len() > 5 {
+        text.push_str(&format!(" and {} more", names.len() - 5));
+    }
+    text
+}
+
+/// Evaluate all rules for a result and an optional baseline
+pub fn evaluate(
+    rules: &GateRules,
+    tree: &Element,
+    baseline: Option<&Element>,
+) -> anyhow::Result<Vec<Check>> {
+    let mut checks = Vec::new();
+    let failed = failed_tests(tree);
+
+    if let Some(max) = rules.max_failures {
+        checks.push(Check::new(
+            format!("At most {max} failed tests"),
+            failed.len() <= max,
+            format!("{} failed", failed.len()),
+        ));
+    }
+
+    for rate in rules.min_pass_rate.iter() {
+        let rule = format!("Pass rate of tag {} at least {}%", rate.tag, rate.percent);
+        let stat = tree
+            .summary
+            .as_ref()
+            .and_then(|s| s.tags.iter().find(|t| t.name == rate.tag));
+        match stat {
+            Some(stat) if stat.pass + stat.fail > 0 => {
+                let percent = 100.0 * stat.pass as f64 / (stat.pass + stat.fail) as f64;
+                checks.push(Check::new(
+                    rule,
+                    percent >= rate.percent,
+                    format!("{percent:.1}% of {} tests", stat.pass + stat.fail),
+                ));
+            }
+            Some(_) => checks.push(Check::new(rule, true, "no tests ran".to_string())),
+            None => checks.push(Check::new(rule, false, "tag not found".to_string())),
+        }
+    }
+
+    if rules.no_new_failures || rules.max_duration_growth.is_some() {
+        let baseline =
+            baseline.ok_or_else(|| anyhow!("The rules need a baseline to compare with"))?;
+        if rules.no_new_failures {
+            let known = failed_tests(baseline);
+            let new: Vec<String> = failed
+                .iter()
+                .filter(|t| !known.contains(t))
+                .cloned()
+                .collect();
+            let detail = if new.is_empty() {
+                "none".to_string()
+            } else {
+                listing(&new)
+            };
+            checks.push(Check::new(
+                "No new failures".to_string(),
+                new.is_empty(),
+                detail,
+            ));
+        }
+        if let Some(max) = rules.max_duration_growth {
+            let rule = format!("Duration growth at most {max}%");
+            match (run_duration(tree), run_duration(baseline)) {
+                (Some(current), Some(before)) if before > 0.0 => {
+                    let growth = 100.0 * (current - before) / before;
+                    checks.push(Check::new(
+                        rule,
+                        growth <= max,
+                        format!("{before:.3}s -> {current:.3}s ({growth:+.1}%)"),
+                    ));
+                }
+                _ => checks.push(Check::new(rule, false, "duration unknown".to_string())),
+            }
+        }
+    }
+
+    if rules.no_suite_errors {
+        let errors = suite_errors(tree);
+        let detail = if errors.is_empty() {
+            "none".to_string()
+        } else {
+            listing(&errors)
+        };
+        checks.push(Check::new(
+            "No suite errors".to_string(),
+            errors.is_empty(),
+            detail,
+        ));
+    }
+    Ok(checks)
+}
+
+/// Checklist for the terminal
+pub fn checks_to_terminal(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks.iter() {
+        let mark = if check.passed {
+            "[PASS]".green()
+        } else {
+            "[FAIL]".red()
+        };
+        out.push_str(&format!("{} {}: {}\n", mark, check.rule, check.detail));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::known_issues::KnownIssues;
+    use crate::summary::{ExecutionMessage, RunSummary, Stat};
+    use std::rc::Rc;
+
+    fn run(results: &[(&str, ResultType)], elapsed: f64) -> Element {
+        let mut root = Element::new(ElementType::Robot, String::new());
+        let mut suite = Element::new(ElementType::Suite, "Suite".to_string());
+        suite.elapsed = Some(elapsed);
+        for (name, result) in results {
+            let mut test = Element::new(ElementType::Test, name.to_string());
+            test.result = result.clone();
+            suite.children.borrow_mut().push(Rc::new(test));
+        }
+        let mut teardown = Element::new(ElementType::Teardown, "Close".to_string());
+        teardown.result = ResultType::Pass;
+        suite.children.borrow_mut().push(Rc::new(teardown));
+        root.children.borrow_mut().push(Rc::new(suite));
+        root.summary = Some(Rc::new(RunSummary {
+            tags: vec![Stat {
+                name: "smoke".to_string(),
+                pass: 3,
+                fail: 1,
+                skip: 0,
+            }],
+            errors: vec![ExecutionMessage {
+                level: "WARN".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }));
+        root
+    }
+
+    #[test]
+    fn evaluate_rules() -> anyhow::Result<()> {
+        let rules = GateRules::from_toml(
+            r#"
+            max_failures = 1
+            no_new_failures = true
+            max_duration_growth = 10.0
+            no_suite_errors = true
+
+            [[min_pass_rate]]
+            tag = "smoke"
+            percent = 80.0
+
+            [[min_pass_rate]]
+            tag = "missing"
+            percent = 50.0
+            "#,
+        )?;
+        let baseline = run(&[("A", ResultType::Fail), ("B", ResultType::Pass)], 10.0);
+        let current = run(&[("A", ResultType::Fail), ("B", ResultType::Fail)], 10.5);
+        let checks = evaluate(&rules, &current, Some(&baseline))?;
+        let outcome: Vec<(&str, bool)> = checks
+            .iter()
+            .map(|c| (c.detail.as_str(), c.passed))
+            .collect();
+        assert_eq!(
+            outcome,
+            vec![
+                ("2 failed", false),
+                ("75.0% of 4 tests", false),
+                ("tag not found", false),
+                ("Suite.B", false),
+                ("10.000s -> 10.500s (+5.0%)", true),
+                ("none", true),
+            ]
+        );
+        assert!(evaluate(&rules, &current, None).is_err());
+
+        // Known issues neither count as failure nor as new failure
+        let issues = KnownIssues::from_toml(
+            r#"
+            [[issue]]
+            id = "BUG-1"
+            state = "flaky"
+            test = "Suite.B"
+            "#,
+        )?;
+        let current = issues.annotate(&current);
+        let rules = GateRules::from_toml("max_failures = 1\nno_new_failures = true")?;
+        let checks = evaluate(&rules, &current, Some(&baseline))?;
+        assert!(checks.iter().all(|c| c.passed));
+
+        let mut root = Element::new(ElementType::Robot, String::new());
+        let suite = Element::new(ElementType::Suite, "Suite".to_string());
+        let mut setup = Element::new(ElementType::Setup, "Open".to_string());
+        setup.result = ResultType::Fail;
+        suite.children.borrow_mut().push(Rc::new(setup));
+        root.children.borrow_mut().push(Rc::new(suite));
+        root.summary = Some(Rc::new(RunSummary {
+            errors: vec![ExecutionMessage {
+                level: "ERROR".to_string(),
+                text: "Importing library 'Missing' failed".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }));
+        assert_eq!(
+            suite_errors(&root),
+            vec![
+                "Setup of suite Suite failed".to_string(),
+                "Importing library 'Missing' failed".to_string()
+            ]
+        );
+
+        assert!(GateRules::from_toml("max_failure = 1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+use std::fs;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context};
+use log::debug;
+use rusqlite::{params, Connection};
+
+use crate::element::{Element, ElementType};
+use crate::rf_parser::{generated_timestamp, parse_tree};
+
+/// A single run stored in the history database
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    pub run_id: String,
+    /// Normalised generation time of the output file
+    pub timestamp: String,
+    pub branch: String,
+    /// Path of the imported file
+    pub source: String,
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Selection of runs from the history.
+/// Runs are returned oldest first so they can be blended directly.
+#[derive(Debug, Default)]
+pub struct RunQuery {
+    pub branch: Option<String>,
+    /// Only the latest N matching runs
+    pub last: Option<usize>,
+    /// All given key value pairs have to match
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Persistent result history in a SQLite file.
+/// Element trees are stored as pre-order list with their depth.
+pub struct History {
+    conn: Connection,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        run_id TEXT PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        branch TEXT NOT NULL,
+        source TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS metadata (
+        run_id TEXT NOT NULL REFERENCES runs(run_id) ON DELETE CASCADE,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS elements (
+        run_id TEXT NOT NULL REFERENCES runs(run_id) ON DELETE CASCADE,
+        seq INTEGER NOT NULL,
+        depth INTEGER NOT NULL,
+        et TEXT NOT NULL,
+        name TEXT NOT NULL,
+        result TEXT NOT NULL,
+        message TEXT NOT NULL,
+        elapsed REAL,
+        owner TEXT NOT NULL,
+        PRIMARY KEY (run_id, seq)
+    );
+    CREATE INDEX IF NOT EXISTS runs_branch ON runs(branch, timestamp);
+";
+
+impl History {
+    /// Open or create a history database file
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let conn = Connection::open(path).context(format!("Cannot open history {}", path))?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(History { conn })
+    }
+
+    /// Store a parsed tree, an existing run with the same id is replaced
+    pub fn import(&mut self, run: &RunRecord, tree: &Element) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM runs WHERE run_id = ?1", params![run.run_id])?;
+        tx.execute(
+            "INSERT INTO runs (run_id, timestamp, branch, source) VALUES (?1, ?2, ?3, ?4)",
+            params![run.run_id, run.timestamp, run.branch, run.source],
+        )?;
+        for (key, value) in run.metadata.iter() {
+            tx.execute(
+                "INSERT INTO metadata (run_id, key, value) VALUES (?1, ?2, ?3)",
+                params![run.run_id, key, value],
+            )?;
+        }
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO elements (run_id, seq, depth, et, name, result, message, elapsed, owner)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            let mut seq = 0;
+            let mut stack: Vec<(Rc<Element>, usize)> = tree
+                .children
+                .borrow()
+                .iter()
+                .rev()
+                .map(|c| (c.clone(), 1))
+                .collect();
+            while let Some((element, depth)) = stack.pop() {
+                stmt.execute(params![
+                    run.run_id,
+                    seq,
+                    depth,
+                    format!("{:?}", element.et),
+                    element.name,
+                    format!("{:?}", element.result),
+                    element.message,
+                    element.elapsed,
+                    &*element.owner,
+                ])?;
+                seq += 1;
+                for child in element.children.borrow().iter().rev() {
+                    stack.push((child.clone(), depth + 1));
+                }
+            }
+            debug!("Imported {} elements as run {}", seq, run.run_id);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn metadata(&self, run_id: &str) -> anyhow::Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM metadata WHERE run_id = ?1 ORDER BY rowid")?;
+        let rows = stmt.query_map(params![run_id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Select runs, oldest first
+    pub fn query(&self, query: &RunQuery) -> anyhow::Result<Vec<RunRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT run_id, timestamp, branch, source FROM runs
+             WHERE ?1 IS NULL OR branch = ?1
+             ORDER BY timestamp DESC, run_id DESC",
+        )?;
+        let rows = stmt.query_map(params![query.branch], |r| {
+            Ok(RunRecord {
+                run_id: r.get(0)?,
+                timestamp: r.get(1)?,
+                branch: r.get(2)?,
+                source: r.get(3)?,
+                metadata: Vec::new(),
+            })
+        })?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            if Some(runs.len()) == query.last {
+                break;
+            }
+            let mut run = row?;
+            run.metadata = self.metadata(&run.run_id)?;
+            if query.metadata.iter().all(|m| run.metadata.contains(m)) {
+                runs.push(run);
+            }
+        }
+        runs.reverse();
+        Ok(runs)
+    }
+
+    /// Rebuild the Element tree of a stored run
+    pub fn load_tree(&self, run_id: &str) -> anyhow::Result<Element> {
+        let mut stmt = self.conn.prepare(
+            "SELECT depth, et, name, result, message, elapsed, owner FROM elements
+             WHERE run_id = ?1 ORDER BY seq",
+        )?;
+        let rows = stmt.query_map(params![run_id], |r| {
+            Ok((
+                r.get::<_, usize>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, String>(4)?,
+                r.get::<_, Option<f64>>(5)?,
+                r.get::<_, String>(6)?,
+            ))
+        })?;
+
+        let root = Element::new(ElementType::Robot, String::new());
+        // Path of open elements from the root, the index is depth - 1
+        let mut stack: Vec<Rc<Element>> = Vec::new();
+        for row in rows {
+            let (depth, et, name, result, message, elapsed, owner) = row?;
+            let mut element = Element::new(et.parse()?, name);
+            element.result = result.parse()?;
+            element.message = message;
+            element.elapsed = elapsed;
+            element.owner = owner.into();
+            let element = Rc::new(element);
+            if depth == 0 || depth > stack.len() + 1 {
+                return Err(anyhow!("Broken tree in history for run {run_id}"));
+            }
+            stack.truncate(depth - 1);
+            match stack.last() {
+                Some(parent) => parent.children.borrow_mut().push(element.clone()),
+                None => root.children.borrow_mut().push(element.clone()),
+            }
+            stack.push(element);
+        }
+        Ok(root)
+    }
+}
+
+/// Parse output files and import them into the history database.
+/// Without a run id the file path is used as id.
+pub fn import_files(
+    db: &str,
+    xml_files: &[String],
+    run_id: Option<&str>,
+    branch: &str,
+    metadata: &[(String, String)],
+) -> anyhow::Result<()> {
+    if run_id.is_some() && xml_files.len() > 1 {
+        return Err(anyhow!("A run id can only be given for a single file"));
+    }
+    let mut history = History::open(db)?;
+    for xml_file in xml_files {
+        eprintln!("Importing {}", xml_file);
+        let xml = fs::read_to_string(xml_file).context(format!("File not found {}", xml_file))?;
+        let tree = parse_tree(&xml)?;
+        let run = RunRecord {
+            run_id: run_id.unwrap_or(xml_file).to_string(),
+            timestamp: generated_timestamp(&xml)?,
+            branch: branch.to_string(),
+            source: xml_file.to_string(),
+            metadata: metadata.to_vec(),
+        };
+        history.import(&run, &tree)?;
+    }
+    Ok(())
+}
+
+/// Load the trees of all runs matching the query, oldest first
+pub fn load_runs(db: &str, query: &RunQuery) -> anyhow::Result<Vec<(RunRecord, Element)>> {
+    let history = History::open(db)?;
+    let mut runs = Vec::new();
+    for run in history.query(query)? {
+        let tree = history.load_tree(&run.run_id)?;
+        runs.push((run, tree));
+    }
+    Ok(runs)
+}
+
+/// Split a `key=value` argument
+pub fn parse_key_val(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) => Ok((key.to_string(), value.to_string())),
+        None => Err(format!("Expected key=value but got {s}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::ResultType;
+
+    fn run(run_id: &str, timestamp: &str, branch: &str) -> RunRecord {
+        RunRecord {
+            run_id: run_id.to_string(),
+            timestamp: timestamp.to_string(),
+            branch: branch.to_string(),
+            source: format!("{run_id}.xml"),
+            metadata: vec![("job".to_string(), "nightly".to_string())],
+        }
+    }
+
+    fn tree() -> Element {
+        let root = Element::new(ElementType::Robot, String::new());
+        let suite = Element::new(ElementType::Suite, "Suite".to_string());
+        let mut test = Element::new(ElementType::Test, "Test".to_string());
+        test.result = ResultType::Fail;
+        test.message = "Boom".to_string();
+        let test = Rc::new(test);
+        let mut keyword = Element::new(ElementType::Keyword, "Fail".to_string());
+        keyword.owner = "BuiltIn".into();
+        test.children.borrow_mut().push(Rc::new(keyword));
+        suite.children.borrow_mut().push(test);
+        suite.children.borrow_mut().push(Rc::new(Element::new(
+            ElementType::Test,
+            "Other".to_string(),
+        )));
+        root.children.borrow_mut().push(Rc::new(suite));
+        root
+    }
+
+    #[test]
+    fn roundtrip_tree() -> anyhow::Result<()> {
+        let mut history = History::open_in_memory()?;
+        let original = tree();
+        history.import(&run("a", "2024-01-01T00:00:00", "main"), &original)?;
+        let loaded = history.load_tree("a")?;
+        assert_eq!(loaded, original);
+        let suite = loaded.children.borrow()[0].clone();
+        assert_eq!(suite.children.borrow()[0].message, "Boom");
+        assert_eq!(
+            suite.children.borrow()[0].children.borrow()[0].qualified_name(),
+            "BuiltIn.Fail"
+        );
+        assert_eq!(&*suite.children.borrow()[1].name, "Other");
+        Ok(())
+    }
+
+    #[test]
+    fn query_runs() -> anyhow::Result<()> {
+        let mut history = History::open_in_memory()?;
+        history.import(&run("a", "2024-01-01T00:00:00", "main"), &tree())?;
+        history.import(&run("b", "2024-01-03T00:00:00", "main"), &tree())?;
+        history.import(&run("c", "2024-01-02T00:00:00", "main"), &tree())?;
+        history.import(&run("d", "2024-01-04T00:00:00", "dev"), &tree())?;
+        // Importing again replaces the run
+        history.import(&run("a", "2024-01-01T00:00:00", "main"), &tree())?;
+
+        let query = RunQuery {
+            branch: Some("main".to_string()),
+            last: Some(2),
+            ..Default::default()
+        };
+        let ids: Vec<String> = history
+            .query(&query)?
+            .into_iter()
+            .map(|r| r.run_id)
+            .collect();
+        assert_eq!(ids, vec!["c", "b"]);
+        let query = RunQuery {
+            last: Some(0),
+            ..Default::default()
+        };
+        assert!(history.query(&query)?.is_empty());
+
+        let query = RunQuery {
+            metadata: vec![("job".to_string(), "nightly".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(history.query(&query)?.len(), 4);
+        let query = RunQuery {
+            metadata: vec![("job".to_string(), "weekly".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(history.query(&query)?.len(), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use log::warn;
+
+use crate::rf_parser::generated_timestamp;
+use crate::snapshot::{is_snapshot, load_snapshot};
+
+/// Path that stands for stdin as input and stdout as output
+pub const STDIO: &str = "-";
+
+/// Read an input file, `-` reads stdin
+pub fn read_input(path: &str) -> anyhow::Result<String> {
+    if path == STDIO {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .context("Cannot read stdin")?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).context(format!("File not found {}", path))
+    }
+}
+
+/// Create an output file, `-` writes to stdout
+pub fn create_output(path: &str) -> anyhow::Result<Box<dyn Write>> {
+    if path == STDIO {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(
+            fs::File::create(path).context(format!("Cannot write {}", path))?,
+        ))
+    }
+}
+
+/// Order of the expanded input files
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum InputOrder {
+    /// Generation time of the output, the path breaks ties
+    Generated,
+    Path,
+}
+
+/// Match a file name against a pattern with `*` and `?`
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last star and the name position it matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn is_pattern(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Output files are named like `output.xml` or `output-3.xml`, snapshots end with `.brs`,
+/// JSON outputs are left out as they cannot be parsed
+fn is_output_file(name: &str) -> bool {
+    wildcard_match("output*.xml", name) || name.ends_with(".brs")
+}
+
+/// Directory entries sorted by name, hidden ones are left out
+fn sorted_entries(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).context(format!("Cannot read {}", dir.display()))? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if !hidden {
+            entries.push(path);
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// All output files below a directory
+pub fn find_outputs(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    for path in sorted_entries(dir)? {
+        if path.is_dir() {
+            found.extend(find_outputs(&path)?);
+        } else if path
+            .file_name()
+            .is_some_and(|n| is_output_file(&n.to_string_lossy()))
+        {
+            found.push(path);
+        }
+    }
+    Ok(found)
+}
+
+fn glob_inner(dir: &Path, parts: &[&str], found: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let Some((part, rest)) = parts.split_first() else {
+        found.push(dir.to_path_buf());
+        return Ok(());
+    };
+    if *part == "**" {
+        // Zero or more directories
+        glob_inner(dir, rest, found)?;
+        for path in sorted_entries(dir)? {
+            if path.is_dir() {
+                glob_inner(&path, parts, found)?;
+            }
+        }
+    } else if !is_pattern(part) {
+        let path = dir.join(part);
+        if (rest.is_empty() && path.exists()) || path.is_dir() {
+            glob_inner(&path, rest, found)?;
+        }
+    } else if dir.is_dir() {
+        for path in sorted_entries(dir)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if wildcard_match(part, &name) && (rest.is_empty() || path.is_dir()) {
+                glob_inner(&path, rest, found)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files matching a pattern with `*`, `?` and `**` for any number of directories
+pub fn expand_glob(pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let (base, pattern) = match pattern.strip_prefix('/') {
+        Some(pattern) => (Path::new("/"), pattern),
+        None => (Path::new("."), pattern),
+    };
+    let parts: Vec<&str> = pattern.split('/').filter(|p| !p.is_empty()).collect();
+    let mut found = Vec::new();
+    glob_inner(base, &parts, &mut found)?;
+    found.dedup();
+    Ok(found
+        .into_iter()
+        .map(|p| p.strip_prefix("./").map(Path::to_path_buf).unwrap_or(p))
+        .collect())
+}
+
+/// Paths listed in a file, one per line. Empty lines and lines starting with `#` are skipped.
+pub fn read_input_list(path: &str) -> anyhow::Result<Vec<String>> {
+    let text = fs::read_to_string(path).context(format!("File not found {}", path))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Replace directories and patterns by the output files they contain.
+/// Without an order the inputs keep their order and expansions are sorted by path.
+pub fn expand_inputs(inputs: &[String], order: Option<InputOrder>) -> anyhow::Result<Vec<String>> {
+    let mut files: Vec<String> = Vec::new();
+    for input in inputs {
+        let expanded = if Path::new(input).is_dir() {
+            let found = find_outputs(Path::new(input))?;
+            if found.is_empty() {
+                return Err(anyhow!("No output files found in {}", input));
+            }
+            found
+        } else if is_pattern(input) {
+            let found = expand_glob(input)?;
+            if found.is_empty() {
+                return Err(anyhow!("No files match {}", input));
+            }
+            found
+        } else {
+            vec![PathBuf::from(input)]
+        };
+        for path in expanded {
+            let path = path.to_string_lossy().to_string();
+            if path.ends_with(".json") {
+                warn!("Skipping {}, JSON output is not supported", path);
+            } else if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    match order {
+        Some(InputOrder::Path) => files.sort(),
+        Some(InputOrder::Generated) => {
+            let mut dated = Vec::new();
+            for file in files {
+                let generated = if is_snapshot(&file) {
+                    let summary = load_snapshot(&file)?.summary;
+                    summary
+                        .map(|s| s.metadata.generated.clone())
+                        .unwrap_or_default()
+                } else {
+                    let xml =
+                        fs::read_to_string(&file).context(format!("File not found {}", file))?;
+                    generated_timestamp(&xml)?
+                };
+                dated.push((generated, file));
+            }
+            dated.sort();
+            files = dated.into_iter().map(|(_, file)| file).collect();
+        }
+        None => (),
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("output*.xml", "output.xml"));
+        assert!(wildcard_match("output*.xml", "output-12.xml"));
+        assert!(!wildcard_match("output*.xml", "output.xml.bak"));
+        assert!(wildcard_match("run?", "run1"));
+        assert!(!wildcard_match("run?", "run"));
+        assert!(wildcard_match("*a*b", "xxaxxab"));
+        assert!(!wildcard_match("*a*b", "xxbxxa"));
+    }
+
+    fn write_output(path: &Path, generated: &str) -> anyhow::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, format!(r#"<robot generated="{generated}"></robot>"#))?;
+        Ok(())
+    }
+
+    #[test]
+    fn expand_directories_and_patterns() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("blend_result_inputs_test");
+        let _ = fs::remove_dir_all(&dir);
+        let runs = dir.join("runs");
+        write_output(
+            &runs.join("2024-02-01/job1/output.xml"),
+            "20240201 10:00:00.000",
+        )?;
+        write_output(
+            &runs.join("2024-01-01/job2/output.xml"),
+            "20240101 09:00:00.000",
+        )?;
+        write_output(
+            &runs.join("2024-01-01/job1/output.xml"),
+            "20240101 10:00:00.000",
+        )?;
+        write_output(
+            &runs.join("2024-01-01/job1/log.xml"),
+            "20240101 10:00:00.000",
+        )?;
+        fs::write(runs.join("2024-01-01/job1/output.json"), "{}")?;
+        let runs = runs.to_string_lossy().to_string();
+
+        let by_path = vec![
+            format!("{runs}/2024-01-01/job1/output.xml"),
+            format!("{runs}/2024-01-01/job2/output.xml"),
+            format!("{runs}/2024-02-01/job1/output.xml"),
+        ];
+        assert_eq!(expand_inputs(std::slice::from_ref(&runs), None)?, by_path);
+        assert_eq!(
+            expand_inputs(&[format!("{runs}/**/output.xml")], None)?,
+            by_path
+        );
+        assert_eq!(
+            expand_inputs(&[format!("{runs}/*/job1/out*.xml")], None)?,
+            vec![by_path[0].clone(), by_path[2].clone()]
+        );
+        assert_eq!(
+            expand_inputs(std::slice::from_ref(&runs), Some(InputOrder::Generated))?,
+            vec![by_path[1].clone(), by_path[0].clone(), by_path[2].clone()]
+        );
+        // Explicit files keep their position and duplicates are dropped
+        assert_eq!(
+            expand_inputs(&[by_path[2].clone(), runs.clone()], None)?,
+            vec![by_path[2].clone(), by_path[0].clone(), by_path[1].clone()]
+        );
+        assert!(expand_inputs(&[format!("{runs}/*/job3/*.xml")], None).is_err());
+
+        let list = dir.join("inputs.txt");
+        fs::write(
+            &list,
+            format!("# nightly\n{}\n\n{}\n", by_path[1], by_path[0]),
+        )?;
+        assert_eq!(
+            read_input_list(&list.to_string_lossy())?,
+            vec![by_path[1].clone(), by_path[0].clone()]
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Symbol table for element names. Keyword names repeat in every test and
+/// every run, each distinct name is stored once and shared by all elements.
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: HashSet<Rc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, name: &str) -> Rc<str> {
+        if let Some(interned) = self.names.get(name) {
+            return interned.clone();
+        }
+        let interned: Rc<str> = Rc::from(name);
+        self.names.insert(interned.clone());
+        interned
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared_names() {
+        let mut names = Interner::default();
+        let a = names.intern("Log");
+        let b = names.intern(&String::from("Log"));
+        assert!(Rc::ptr_eq(&a, &b));
+        assert!(!Rc::ptr_eq(&a, &names.intern("Log Many")));
+        assert_eq!(names.names.len(), 2);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::fs;
+use std::rc::Rc;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::element::{Element, ElementType, ResultType};
+use crate::failures::failure_of;
+use crate::filter::copy_element;
+
+/// Triage state of a known issue
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    /// The failure is expected until the issue is fixed
+    Expected,
+    /// The test is unreliable and its results are ignored
+    Quarantined,
+    /// The test passes and fails without related changes
+    Flaky,
+}
+
+/// A known bug matched by test path and/or failure message
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct KnownIssue {
+    pub id: String,
+    pub state: IssueState,
+    /// Suite path and test name joined by dots, `*` matches any text
+    pub test: Option<String>,
+    /// Failure message, `*` matches any text
+    pub message: Option<String>,
+}
+
+impl fmt::Display for KnownIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?})", self.id, self.state)
+    }
+}
+
+/// Contents of a known issues file
+///
+/// ```toml
+/// [[issue]]
+/// id = "BUG-123"
+/// state = "expected"
+/// test = "Suite.Login*"
+/// message = "*Connection refused*"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct KnownIssues {
+    #[serde(default, rename = "issue")]
+    pub issues: Vec<KnownIssue>,
+}
+
+/// Match a text against a pattern where `*` matches any text
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+impl KnownIssues {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path).context(format!("File not found {}", path))?;
+        Self::from_toml(&text).context(format!("Cannot parse known issues {}", path))
+    }
+
+    pub fn from_toml(text: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// First issue matching a test. Entries with a message pattern only match failed tests.
+    pub fn find(&self, test: &Element, path: &str) -> Option<&KnownIssue> {
+        self.issues.iter().find(|issue| {
+            let path_matches = issue.test.as_ref().is_none_or(|t| glob_match(t, path));
+            let message_matches = match &issue.message {
+                None => true,
+                Some(pattern) => {
+                    test.result == ResultType::Fail
+                        && glob_match(pattern, &failure_of(test, path).message)
+                }
+            };
+            (issue.test.is_some() || issue.message.is_some()) && path_matches && message_matches
+        })
+    }
+
+    /// Copy of a tree with all matching tests annotated
+    pub fn annotate(&self, tree: &Element) -> Element {
+        self.annotate_inner(tree, "")
+    }
+
+    fn annotate_inner(&self, element: &Element, path: &str) -> Element {
+        let mut copy = copy_element(element);
+        if element.et == ElementType::Test {
+            copy.issue = self.find(element, path).cloned().map(Rc::new);
+        }
+        for child in element.children.borrow().iter() {
+            let child_path = match child.et {
+                ElementType::Suite | ElementType::Test if path.is_empty() => child.name.to_string(),
+                ElementType::Suite | ElementType::Test => format!("{}.{}", path, child.name),
+                _ => path.to_string(),
+            };
+            let child = if matches!(child.et, ElementType::Suite | ElementType::Test) {
+                self.annotate_inner(child, &child_path)
+            } else {
+                child.deep_clone()
+            };
+            copy.children.borrow_mut().push(Rc::new(child));
+        }
+        copy
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("Suite.Test", "Suite.Test"));
+        assert!(!glob_match("Suite.Test", "Suite.Test 2"));
+        assert!(glob_match("Suite.*", "Suite.Test 2"));
+        assert!(glob_match("*refused*", "Connection refused to host"));
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(!glob_match("a*b*c", "acb"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn annotate_tests() -> anyhow::Result<()> {
+        let issues = KnownIssues::from_toml(
+            r#"
+            [[issue]]
+            id = "BUG-1"
+            state = "expected"
+            message = "*refused*"
+
+            [[issue]]
+            id = "BUG-2"
+            state = "quarantined"
+            test = "Suite.B*"
+            "#,
+        )?;
+        assert_eq!(issues.issues[1].state, IssueState::Quarantined);
+
+        let root = Element::new(ElementType::Robot, String::new());
+        let suite = Element::new(ElementType::Suite, "Suite".to_string());
+        for (name, result, message) in [
+            ("A", ResultType::Fail, "Connection refused"),
+            ("B", ResultType::Pass, ""),
+            ("C", ResultType::Fail, "Assertion failed"),
+        ] {
+            let mut test = Element::new(ElementType::Test, name.to_string());
+            test.result = result;
+            test.message = message.to_string();
+            suite.children.borrow_mut().push(Rc::new(test));
+        }
+        root.children.borrow_mut().push(Rc::new(suite));
+
+        let annotated = issues.annotate(&root);
+        let suite = annotated.children.borrow()[0].clone();
+        let ids: Vec<Option<String>> = suite
+            .children
+            .borrow()
+            .iter()
+            .map(|t| t.issue.as_ref().map(|i| i.to_string()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                Some("BUG-1 (Expected)".to_string()),
+                Some("BUG-2 (Quarantined)".to_string()),
+                None
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,37 @@
 pub mod blend_results;
+pub mod blend_tree;
+pub mod blender;
+pub mod browse;
+pub mod cache;
+pub mod cluster;
+pub mod config;
 pub mod element;
+pub mod export;
+pub mod failures;
+pub mod filter;
+pub mod gate;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod inputs;
+pub mod interner;
+pub mod known_issues;
+pub mod merge;
+pub mod metadata;
 pub mod multi_result_list;
+pub mod pabot;
+pub mod rerun;
+pub mod result_file;
 pub mod rf_parser;
+pub mod serve;
+pub mod snapshot;
+pub mod summary;
+pub mod timestamp;
+pub mod trend;
+pub mod watch;
 pub use blend_results::*;
+pub use blender::{Align, Blender, Filter};
+pub use export::Exporter;
+pub use interner::Interner;
+pub use multi_result_list::MultiResultList;
+pub use result_file::ResultFile;
 pub use rf_parser::*;
@@ -1,52 +1,741 @@
 use std::fs;
+use std::rc::Rc;
 
 use anyhow::{self, Context};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use log::error;
 
-mod blend_results;
-mod element;
-mod multi_result_list;
-mod rf_parser;
+#[cfg(feature = "history")]
+use blend_result::history;
+use blend_result::{
+    blend_results, blend_tree, browse, cache, cluster, config, element, failures, filter, gate,
+    inputs, known_issues, merge, metadata, multi_result_list, pabot, rerun, rf_parser, serve,
+    snapshot, trend, watch, Blender, Filter,
+};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
+    /// Configuration file instead of the nearest blend_result.toml
+    #[arg(long, global = true)]
+    config: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
 
+impl Cli {
+    /// Fill in the defaults of the configuration where no option is given
+    fn apply_config(&mut self, config: &config::Config) -> anyhow::Result<()> {
+        match &mut self.command {
+            Commands::Blend {
+                depth,
+                output,
+                input,
+                options,
+                inputs,
+                ..
+            } => {
+                // Command lines of earlier versions start with a positional depth
+                if let (None, Ok(old_depth), false) =
+                    (*depth, output.parse::<usize>(), input.is_empty())
+                {
+                    eprintln!("A depth before the output is deprecated, use -d {old_depth}");
+                    *depth = Some(old_depth);
+                    *output = input.remove(0);
+                }
+                *depth = depth.or(config.depth);
+                options.apply_config(config);
+                inputs.apply_config(config);
+            }
+            Commands::Browse {
+                depth,
+                known_issues,
+                levels,
+                ..
+            } => {
+                *depth = depth.or(config.depth);
+                if known_issues.is_none() {
+                    known_issues.clone_from(&config.known_issues);
+                }
+                levels.apply_config(config);
+            }
+            Commands::Watch { depth, options, .. } => {
+                *depth = depth.or(config.depth);
+                options.apply_config(config);
+            }
+            Commands::Serve { depth, inputs, .. } => {
+                *depth = depth.or(config.depth);
+                inputs.apply_config(config);
+            }
+            Commands::Gate { known_issues, .. } if known_issues.is_none() => {
+                known_issues.clone_from(&config.known_issues);
+            }
+            Commands::Trend { format, .. } => {
+                if let (None, Some(name)) = (&format, &config.format) {
+                    *format = Some(
+                        TrendFormat::from_str(name, true)
+                            .map_err(|e| anyhow::anyhow!("Format in configuration: {e}"))?,
+                    );
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// Flatten an output file into CSV
     Parse {
-        filename: Option<String>,
-        output: Option<String>,
+        /// Output file, `-` reads stdin
+        filename: String,
+        /// CSV file, `-` or left out writes to stdout
+        #[arg(default_value = inputs::STDIO)]
+        output: String,
     },
     Blend {
-        depth: usize,
+        /// Depth to blend, 0 for all levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+        output: String,
+        input: Vec<String>,
+        /// Combine an output and its reruns into one column, e.g. run1=a.xml,a_rerun.xml
+        #[arg(long = "group", value_parser = rerun::parse_group)]
+        groups: Vec<rerun::RunGroup>,
+        /// Stitch the partial outputs of a pabot results directory into one column
+        #[arg(long)]
+        pabot: Vec<String>,
+        /// Exit with 1 when a test failed in the last input that passed before
+        #[arg(long)]
+        fail_on_regression: bool,
+        #[command(flatten)]
+        options: BlendOptions,
+        #[command(flatten)]
+        inputs: InputSelection,
+        #[cfg(feature = "history")]
+        #[command(flatten)]
+        history: HistorySelection,
+    },
+    /// Browse blended results in the terminal
+    Browse {
+        /// Depth to blend, 0 for all levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+        input: Vec<String>,
+        /// Annotate tests listed in a known issues file and skip them as regressions
+        #[arg(long)]
+        known_issues: Option<String>,
+        #[command(flatten)]
+        levels: LevelSelection,
+    },
+    /// Merge output files into a new output.xml
+    Merge {
+        output: String,
+        input: Vec<String>,
+        /// Put all results below a new top level suite instead of merging reruns
+        #[arg(long, conflicts_with = "stitch")]
+        concat: bool,
+        /// Combine partial outputs of parallel runs like pabot ordered by suite source
+        #[arg(long)]
+        stitch: bool,
+        /// Name of the top level suite
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Store an output as compact snapshot without log messages, blend reads snapshots like outputs
+    Convert {
+        /// Output file, `-` reads stdin
+        input: String,
         output: String,
+    },
+    /// Show the failing keyword chain and message of each failed test
+    Failures {
+        /// Write a CSV file instead of printing
+        #[arg(short, long)]
+        output: Option<String>,
+        input: Vec<String>,
+    },
+    /// Group failed tests of all inputs by their normalised failure message
+    Clusters {
+        /// Tests listed per cluster
+        #[arg(long, default_value_t = 10)]
+        max_tests: usize,
+        input: Vec<String>,
+    },
+    /// Check a result against quality gate rules, exits with 1 when a rule fails
+    Gate {
+        /// Rules file, defaults to the gate table of the configuration
+        #[arg(short, long)]
+        rules: Option<String>,
+        /// Earlier result to compare with for new failures and duration growth
+        #[arg(long)]
+        baseline: Option<String>,
+        /// Tests of known issues do not count as failures
+        #[arg(long)]
+        known_issues: Option<String>,
+        input: String,
+    },
+    /// Local web server to browse blended results and their history
+    Serve {
+        /// Address to listen on, 0.0.0.0:8000 shares it within the LAN
+        #[arg(long, default_value = "127.0.0.1:8000")]
+        bind: String,
+        /// Depth to blend when the page does not select one, 0 for all levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+        #[command(flatten)]
+        inputs: InputSelection,
+        input: Vec<String>,
+    },
+    /// Blend a results directory again whenever a new output is finished
+    Watch {
+        /// Depth to blend, 0 for all levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+        /// Seconds between looking for new outputs
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+        /// Also write a HTML trend report of all outputs
+        #[arg(long)]
+        html: Option<String>,
+        output: String,
+        dir: String,
+        #[command(flatten)]
+        options: BlendOptions,
+    },
+    /// Per test trend over many runs
+    Trend {
+        /// Output format, terminal when not configured
+        #[arg(short, long, value_enum)]
+        format: Option<TrendFormat>,
+        /// Write to a file instead of the terminal
+        #[arg(short, long)]
+        output: Option<String>,
         input: Vec<String>,
+        #[cfg(feature = "history")]
+        #[command(flatten)]
+        history: HistorySelection,
     },
+    /// Result history database
+    #[cfg(feature = "history")]
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+enum TrendFormat {
+    Terminal,
+    Csv,
+    Html,
+}
+
+#[cfg(feature = "history")]
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Import result files into the history
+    Import {
+        #[arg(long, default_value = "history.db")]
+        db: String,
+        /// Id of the run, defaults to the file path
+        #[arg(long)]
+        run_id: Option<String>,
+        #[arg(long, default_value = "")]
+        branch: String,
+        /// Arbitrary metadata as key=value
+        #[arg(long = "meta", value_parser = history::parse_key_val)]
+        meta: Vec<(String, String)>,
+        input: Vec<String>,
+    },
+    /// List runs in the history
+    Query {
+        #[arg(long, default_value = "history.db")]
+        db: String,
+        #[arg(long)]
+        branch: Option<String>,
+        /// Only the latest N runs
+        #[arg(long)]
+        last: Option<usize>,
+        /// Only runs with the metadata key=value
+        #[arg(long = "meta", value_parser = history::parse_key_val)]
+        meta: Vec<(String, String)>,
+    },
+}
+
+/// Labels, filters and exports shared by blend and watch
+#[derive(clap::Args)]
+struct BlendOptions {
+    /// Column header for one of the inputs, e.g. nightly-42=output.xml
+    #[arg(long = "label", value_parser = metadata::parse_label)]
+    labels: Vec<(String, String)>,
+    /// Leave out suite and test setups and teardowns
+    #[arg(long, conflicts_with = "teardown_failures")]
+    hide_setup_teardown: bool,
+    /// Keep setups and teardowns hidden by the configuration
+    #[arg(long, conflicts_with = "hide_setup_teardown")]
+    no_hide_setup_teardown: bool,
+    /// Only report teardowns that failed in any input
+    #[arg(long)]
+    teardown_failures: bool,
+    /// Report all teardowns although the configuration selects failed ones
+    #[arg(long, conflicts_with = "teardown_failures")]
+    no_teardown_failures: bool,
+    /// Only expand rows that differ or did not pass
+    #[arg(long)]
+    diff_only: bool,
+    /// Expand all rows although the configuration sets diff_only
+    #[arg(long, conflicts_with = "diff_only")]
+    no_diff_only: bool,
+    /// Add a column with the failing keyword chain of failed tests
+    #[arg(long)]
+    failures: bool,
+    /// Leave out the failure column although the configuration sets failures
+    #[arg(long, conflicts_with = "failures")]
+    no_failures: bool,
+    /// Annotate tests listed in a known issues file and skip them as regressions
+    #[arg(long)]
+    known_issues: Option<String>,
+    #[command(flatten)]
+    levels: LevelSelection,
+    /// Also write the blend with a Summary sheet into an ods file
+    #[cfg(feature = "odson")]
+    #[arg(long)]
+    ods: Option<String>,
+}
+
+impl BlendOptions {
+    fn apply_config(&mut self, config: &config::Config) {
+        for (label, path) in config.labels.iter() {
+            if !self.labels.iter().any(|(l, p)| l == label || p == path) {
+                self.labels.push((label.clone(), path.clone()));
+            }
+        }
+        // An explicit filter replaces the conflicting one of the configuration
+        self.hide_setup_teardown = !self.no_hide_setup_teardown
+            && (self.hide_setup_teardown || config.hide_setup_teardown && !self.teardown_failures);
+        self.teardown_failures = !self.no_teardown_failures
+            && (self.teardown_failures || config.teardown_failures && !self.hide_setup_teardown);
+        self.diff_only = !self.no_diff_only && (self.diff_only || config.diff_only);
+        self.failures = !self.no_failures && (self.failures || config.failures);
+        if self.known_issues.is_none() {
+            self.known_issues.clone_from(&config.known_issues);
+        }
+        self.levels.apply_config(config);
+    }
+
+    /// Blender with the filters in the order known issues, setups and teardowns, levels
+    fn blender(&self, depth: usize) -> anyhow::Result<Blender> {
+        let mut blender = Blender::new()
+            .max_depth(depth)
+            .diff_only(self.diff_only)
+            .failure_column(self.failures);
+        if let Some(path) = &self.known_issues {
+            let issues = known_issues::KnownIssues::load(path)?;
+            blender = blender.filter(Filter::KnownIssues(Rc::new(issues)));
+        }
+        if self.hide_setup_teardown {
+            blender = blender.filter(Filter::HideSetupTeardown);
+        } else if self.teardown_failures {
+            blender = blender.filter(Filter::TeardownFailures);
+        }
+        if let Some(filter) = self.levels.filter() {
+            blender = blender.filter(filter);
+        }
+        Ok(blender)
+    }
+
+    /// Write the CSV and the requested ods export
+    fn save(&self, mrl: &multi_result_list::MultiResultList, output: &str) -> anyhow::Result<()> {
+        blend_results::save_to_csv(mrl, output)?;
+        #[cfg(feature = "odson")]
+        if let Some(ods) = &self.ods {
+            blend_results::save_to_ods(mrl, ods)?;
+        }
+        Ok(())
+    }
+}
+
+/// Element type based depth control, independent of how deep suites are nested
+#[derive(clap::Args)]
+struct LevelSelection {
+    /// Stop descending at this element type
+    #[arg(long, value_enum)]
+    level: Option<filter::Level>,
+    /// Levels of keywords below a test, implies --level keyword
+    #[arg(long)]
+    keyword_depth: Option<usize>,
+}
+
+impl LevelSelection {
+    fn apply_config(&mut self, config: &config::Config) {
+        self.level = self.level.or(config.level);
+        self.keyword_depth = self.keyword_depth.or(config.keyword_depth);
+    }
+
+    fn apply(&self, trees: Vec<element::Element>) -> Vec<element::Element> {
+        let Some(Filter::Level {
+            level,
+            keyword_depth,
+        }) = self.filter()
+        else {
+            return trees;
+        };
+        trees
+            .iter()
+            .map(|tree| filter::limit_levels(tree, level, keyword_depth))
+            .collect()
+    }
+
+    fn filter(&self) -> Option<Filter> {
+        let level = match (self.level, self.keyword_depth) {
+            (Some(level), _) => level,
+            (None, Some(_)) => filter::Level::Keyword,
+            (None, None) => return None,
+        };
+        Some(Filter::Level {
+            level,
+            keyword_depth: self.keyword_depth,
+        })
+    }
+}
+
+/// Input files given as directories, patterns or in a list file
+#[derive(clap::Args)]
+struct InputSelection {
+    /// File with one input path, directory or pattern per line
+    #[arg(long)]
+    inputs_from: Option<String>,
+    /// Sort all inputs instead of keeping the given order
+    #[arg(long, value_enum)]
+    sort: Option<inputs::InputOrder>,
+    /// Always parse the inputs instead of reusing cached trees
+    #[arg(long)]
+    no_cache: bool,
+    /// Directory of the parse cache, defaults to ~/.cache/blend_result
+    #[arg(long)]
+    cache_dir: Option<String>,
+}
+
+impl InputSelection {
+    fn apply_config(&mut self, config: &config::Config) {
+        if self.cache_dir.is_none() {
+            self.cache_dir.clone_from(&config.cache_dir);
+        }
+    }
+
+    fn expand(&self, input: &[String]) -> anyhow::Result<Vec<String>> {
+        let mut input = input.to_vec();
+        if let Some(list) = &self.inputs_from {
+            input.extend(inputs::read_input_list(list)?);
+        }
+        inputs::expand_inputs(&input, self.sort)
+    }
+
+    fn parse_files(&self, files: &[String]) -> anyhow::Result<Vec<element::Element>> {
+        if self.no_cache {
+            return blend_results::parse_files(files);
+        }
+        let dir = match &self.cache_dir {
+            Some(dir) => std::path::PathBuf::from(dir),
+            None => cache::ParseCache::default_dir(),
+        };
+        cache::ParseCache::new(&dir).parse_files(files)
+    }
+}
+
+/// Runs from the history that are blended after the input files
+#[cfg(feature = "history")]
+#[derive(clap::Args)]
+struct HistorySelection {
+    /// History database to select runs from
+    #[arg(long = "history")]
+    db: Option<String>,
+    #[arg(long, requires = "db")]
+    branch: Option<String>,
+    /// Only the latest N runs
+    #[arg(long, requires = "db")]
+    last: Option<usize>,
+    /// Only runs with the metadata key=value
+    #[arg(long = "meta", requires = "db", value_parser = history::parse_key_val)]
+    meta: Vec<(String, String)>,
+}
+
+#[cfg(feature = "history")]
+impl HistorySelection {
+    fn query(&self) -> history::RunQuery {
+        history::RunQuery {
+            branch: self.branch.clone(),
+            last: self.last,
+            metadata: self.meta.clone(),
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     //simple_logger::SimpleLogger::new().env().init().unwrap();
     simple_logger::init_with_level(log::Level::Warn).unwrap();
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let config = config::Config::discover(cli.config.as_deref())?;
+    cli.apply_config(&config)?;
     match &cli.command {
         Commands::Parse { filename, output } => {
-            println!("Parsing {}", filename.as_ref().unwrap());
-            let xml = fs::read_to_string(filename.as_ref().unwrap()).context("Reading failed")?;
-            rf_parser::parse(&xml, &output.as_ref().unwrap())?;
+            eprintln!("Parsing {}", filename);
+            let xml = inputs::read_input(filename)?;
+            rf_parser::parse(&xml, output)?;
         }
         Commands::Blend {
             input,
             output,
             depth,
+            groups,
+            pabot,
+            fail_on_regression,
+            options,
+            inputs,
+            #[cfg(feature = "history")]
+            history,
+        } => {
+            let input = inputs.expand(input)?;
+            eprintln!("Blending {:?} {}", input, output);
+            let mut trees = inputs.parse_files(&input)?;
+            let mut names = metadata::column_labels(&input, &trees, &options.labels);
+            for group in groups {
+                let group_trees = inputs.parse_files(&group.files)?;
+                if let Some(tree) = rerun::combine_reruns(group_trees) {
+                    names.push(group.name.clone());
+                    trees.push(tree);
+                }
+            }
+            for dir in pabot {
+                names.push(dir.clone());
+                trees.push(pabot::parse_pabot_dir(dir)?);
+            }
+            #[cfg(feature = "history")]
+            if let Some(db) = &history.db {
+                for (run, tree) in history::load_runs(db, &history.query())? {
+                    eprintln!("Loading {} from history", run.run_id);
+                    names.push(run.run_id);
+                    trees.push(tree);
+                }
+            }
+            let trees: Vec<&element::Element> = trees.iter().collect();
+            let mrl = options
+                .blender(depth.unwrap_or(0))?
+                .run_trees(&names, &trees)?;
+            options.save(&mrl, output)?;
+            eprintln!("{} regressions", mrl.regressions.len());
+            if *fail_on_regression && !mrl.regressions.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Browse {
+            depth,
+            input,
+            known_issues,
+            levels,
+        } => {
+            let mut trees = blend_results::parse_files(input)?;
+            if let Some(path) = known_issues {
+                let issues = known_issues::KnownIssues::load(path)?;
+                trees = trees.iter().map(|t| issues.annotate(t)).collect();
+            }
+            let trees = levels.apply(trees);
+            let trees_to_blend: Vec<Option<&element::Element>> = trees.iter().map(Some).collect();
+            let nodes = blend_tree::blend_tree(&trees_to_blend, depth.unwrap_or(0));
+            browse::Browser::new(nodes, input.clone()).run()?;
+        }
+        Commands::Merge {
+            output,
+            input,
+            concat,
+            stitch,
+            name,
+        } => {
+            let mode = if *concat {
+                merge::MergeMode::Concatenate
+            } else if *stitch {
+                merge::MergeMode::Stitch
+            } else {
+                merge::MergeMode::Merge
+            };
+            merge::merge_files(input, output, mode, name.as_deref())?;
+        }
+        Commands::Convert { input, output } => {
+            eprintln!("Parsing {}", input);
+            let xml = inputs::read_input(input)?;
+            let tree = rf_parser::parse_tree(&xml)?;
+            snapshot::save_snapshot(&tree, output)?;
+            let size = fs::metadata(output)?.len();
+            eprintln!("Wrote {} with {} of {} bytes", output, size, xml.len());
+        }
+        Commands::Failures { output, input } => {
+            let trees = blend_results::parse_files(input)?;
+            let failures: Vec<_> = trees.iter().map(failures::find_failures).collect();
+            match output {
+                Some(file) => fs::write(file, failures::failures_to_csv(&failures, input)?)
+                    .context(format!("Cannot write {}", file))?,
+                None => print!("{}", failures::failures_to_terminal(&failures, input)),
+            }
+        }
+        Commands::Clusters { max_tests, input } => {
+            let trees = blend_results::parse_files(input)?;
+            let clusters = cluster::cluster_failures(&trees);
+            print!(
+                "{}",
+                cluster::clusters_to_terminal(&clusters, input, *max_tests)
+            );
+        }
+        Commands::Gate {
+            rules,
+            baseline,
+            known_issues,
+            input,
+        } => {
+            let loaded;
+            let rules = match rules {
+                Some(path) => {
+                    loaded = gate::GateRules::load(path)?;
+                    &loaded
+                }
+                None => config.gate.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No gate rules, use --rules or a gate table in the configuration"
+                    )
+                })?,
+            };
+            let mut files = vec![input.clone()];
+            files.extend(baseline.iter().cloned());
+            let mut trees = blend_results::parse_files(&files)?;
+            if let Some(path) = known_issues {
+                let issues = known_issues::KnownIssues::load(path)?;
+                trees = trees.iter().map(|t| issues.annotate(t)).collect();
+            }
+            let checks = gate::evaluate(rules, &trees[0], trees.get(1))?;
+            print!("{}", gate::checks_to_terminal(&checks));
+            if checks.iter().any(|c| !c.passed) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Serve {
+            bind,
+            depth,
+            inputs,
+            input,
+        } => {
+            let input = inputs.expand(input)?;
+            let trees = inputs.parse_files(&input)?;
+            let names = metadata::column_labels(&input, &trees, &[]);
+            let server = serve::Server::new(names, trees, depth.unwrap_or(0));
+            server.run(bind)?;
+        }
+        Commands::Watch {
+            depth,
+            interval,
+            html,
+            output,
+            dir,
+            options,
+        } => {
+            let blender = options.blender(depth.unwrap_or(0))?;
+            let mut watcher = watch::Watcher::new(dir).with_labels(&options.labels);
+            // A failed blend is tried again on the next poll, e.g. when the output is locked
+            let mut outdated = false;
+            loop {
+                match watcher.poll() {
+                    Ok(added) => {
+                        for file in added.iter() {
+                            eprintln!("Added {}", file);
+                        }
+                        outdated |= !added.is_empty();
+                    }
+                    Err(e) => error!("Cannot search {}: {:#}", dir, e),
+                }
+                if outdated {
+                    let saved = blender
+                        .run_trees(&watcher.names, &watcher.trees.iter().collect::<Vec<_>>())
+                        .and_then(|mrl| options.save(&mrl, output))
+                        .and_then(|_| watcher.save_trend(html.as_deref()));
+                    match saved {
+                        Ok(()) => {
+                            outdated = false;
+                            eprintln!("Blended {} outputs into {}", watcher.trees.len(), output);
+                        }
+                        Err(e) => error!("Cannot blend into {}: {:#}", output, e),
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_secs(*interval));
+            }
+        }
+        Commands::Trend {
+            format,
+            output,
+            input,
+            #[cfg(feature = "history")]
+            history,
         } => {
-            println!("Blending {:?} {}", input, output);
-            blend_results::blend_and_save_to_csv(input, output, *depth)?;
+            let (mut runs, mut trees) = trend::load_runs(input)?;
+            #[cfg(feature = "history")]
+            if let Some(db) = &history.db {
+                for (run, tree) in history::load_runs(db, &history.query())? {
+                    runs.push(trend::RunInfo {
+                        label: run.run_id,
+                        date: run.timestamp,
+                    });
+                    trees.push(tree);
+                }
+            }
+            let report = trend::TrendReport::new(runs, &trees);
+            let text = match format.as_ref().unwrap_or(&TrendFormat::Terminal) {
+                TrendFormat::Terminal => report.to_terminal(),
+                TrendFormat::Csv => report.to_csv()?,
+                TrendFormat::Html => report.to_html(),
+            };
+            match output {
+                Some(file) => fs::write(file, text).context(format!("Cannot write {}", file))?,
+                None => print!("{}", text),
+            }
         }
+        #[cfg(feature = "history")]
+        Commands::History { command } => match command {
+            HistoryCommands::Import {
+                db,
+                run_id,
+                branch,
+                meta,
+                input,
+            } => {
+                history::import_files(db, input, run_id.as_deref(), branch, meta)?;
+            }
+            HistoryCommands::Query {
+                db,
+                branch,
+                last,
+                meta,
+            } => {
+                let query = history::RunQuery {
+                    branch: branch.clone(),
+                    last: *last,
+                    metadata: meta.clone(),
+                };
+                for run in history::History::open(db)?.query(&query)? {
+                    let meta: Vec<String> = run
+                        .metadata
+                        .iter()
+                        .map(|(k, v)| format!("{k}={v}"))
+                        .collect();
+                    println!(
+                        "{:<32} {:<26} {:<16} {}",
+                        run.run_id,
+                        run.timestamp,
+                        run.branch,
+                        meta.join(" ")
+                    );
+                }
+            }
+        },
     }
     Ok(())
 }
@@ -0,0 +1,871 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{anyhow, Context};
+use log::warn;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+use crate::rf_parser::get_attribute_kv;
+use crate::timestamp::{normalize_timestamp, timestamp_seconds};
+
+/// Generic XML element, used to keep everything of an output.xml when merging
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+/// How multiple outputs are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeMode {
+    /// Like `rebot --merge`, tests of later outputs replace tests with the same name
+    Merge,
+    /// All top level suites are put below a new top level suite
+    Concatenate,
+    /// Partial outputs of parallel processes e.g. from pabot are combined
+    /// into one suite tree ordered by suite source
+    Stitch,
+}
+
+impl XmlElement {
+    pub fn new(name: &str) -> Self {
+        XmlElement {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_attr(&mut self, key: &str, value: &str) {
+        match self.attributes.iter_mut().find(|(k, _)| k == key) {
+            Some(a) => a.1 = value.to_string(),
+            None => self.attributes.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut XmlElement> {
+        self.elements_mut().find(|e| e.name == name)
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut XmlElement> {
+        self.children.iter_mut().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Concatenated text content of the element
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|c| match c {
+                XmlNode::Text(t) => Some(t.as_str()),
+                XmlNode::Element(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.children.retain(|c| matches!(c, XmlNode::Element(_)));
+        if !text.is_empty() {
+            self.children.push(XmlNode::Text(text.to_string()));
+        }
+    }
+
+    /// Status attribute of the status child element
+    fn status(&self) -> &str {
+        self.child("status")
+            .and_then(|s| s.attr("status"))
+            .unwrap_or("")
+    }
+
+    fn set_status(&mut self, status: &str) {
+        if let Some(s) = self.child_mut("status") {
+            s.set_attr("status", status);
+        }
+    }
+}
+
+fn start_to_element(decoder: quick_xml::encoding::Decoder, e: &BytesStart) -> XmlElement {
+    XmlElement {
+        name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+        attributes: e
+            .attributes()
+            .map(|a| get_attribute_kv(decoder, a))
+            .collect(),
+        children: Vec::new(),
+    }
+}
+
+/// Whitespace between child elements is only layout, the text of elements
+/// without children like `<msg>` or `<doc>` is kept unmodified
+fn drop_layout_text(element: &mut XmlElement) {
+    if element.elements().next().is_some() {
+        element
+            .children
+            .retain(|c| !matches!(c, XmlNode::Text(t) if t.trim().is_empty()));
+    }
+}
+
+/// Read a XML document into a tree of XmlElements and return the root element
+pub fn read_xml(xml_data: &str) -> anyhow::Result<XmlElement> {
+    let mut reader = Reader::from_str(xml_data);
+    let decoder = reader.decoder();
+    let mut stack: Vec<XmlElement> = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => stack.push(start_to_element(decoder, &e)),
+            Event::Empty(e) => {
+                let element = start_to_element(decoder, &e);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(XmlNode::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+            Event::Text(e) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent
+                        .children
+                        .push(XmlNode::Text(e.unescape()?.to_string()));
+                }
+            }
+            Event::CData(e) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = String::from_utf8_lossy(&e.into_inner()).to_string();
+                    parent.children.push(XmlNode::Text(text));
+                }
+            }
+            Event::End(_) => {
+                let mut element = stack.pop().ok_or(anyhow!("Unbalanced XML"))?;
+                drop_layout_text(&mut element);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(XmlNode::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+            Event::Eof => return Err(anyhow!("No root element found")),
+            _ => (),
+        }
+    }
+}
+
+/// Write a tree of XmlElements in the layout of robotframework,
+/// every element starts on a new line and text is kept inline.
+pub fn write_xml(root: &XmlElement) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    write_element(root, &mut out);
+    out
+}
+
+fn write_element(element: &XmlElement, out: &mut String) {
+    out.push('<');
+    out.push_str(&element.name);
+    for (key, value) in element.attributes.iter() {
+        out.push_str(&format!(" {}=\"{}\"", key, escape(value.as_str())));
+    }
+    if element.children.is_empty() {
+        out.push_str("/>\n");
+        return;
+    }
+    out.push('>');
+    if element.elements().next().is_some() {
+        out.push('\n');
+    }
+    for child in element.children.iter() {
+        match child {
+            XmlNode::Element(e) => write_element(e, out),
+            XmlNode::Text(t) => out.push_str(&escape(t.as_str())),
+        }
+    }
+    out.push_str(&format!("</{}>\n", element.name));
+}
+
+/// Combine multiple output.xml roots into one
+pub fn merge_outputs(
+    outputs: Vec<XmlElement>,
+    mode: MergeMode,
+    name: Option<&str>,
+) -> anyhow::Result<XmlElement> {
+    let mut outputs = outputs.into_iter();
+    let mut root = outputs.next().ok_or(anyhow!("Nothing to merge"))?;
+    let rest: Vec<XmlElement> = outputs.collect();
+    for other in rest.iter() {
+        if other.attr("schemaversion") != root.attr("schemaversion") {
+            warn!(
+                "Merging outputs with different schema versions {:?} and {:?}",
+                root.attr("schemaversion"),
+                other.attr("schemaversion")
+            );
+        }
+    }
+
+    let mut errors = take_children(&mut root, "errors");
+    for other in rest.iter() {
+        if let Some(e) = other.child("errors") {
+            errors.extend(e.children.iter().cloned());
+        }
+    }
+
+    match mode {
+        MergeMode::Merge => {
+            let suite = root
+                .child_mut("suite")
+                .ok_or(anyhow!("Output without suite"))?;
+            for other in rest.iter() {
+                let other_suite = other
+                    .child("suite")
+                    .ok_or(anyhow!("Output without suite"))?;
+                if other_suite.attr("name") != suite.attr("name") {
+                    return Err(anyhow!(
+                        "Cannot merge outputs containing different root suites {:?} and {:?}",
+                        suite.attr("name"),
+                        other_suite.attr("name")
+                    ));
+                }
+                merge_suite(suite, other_suite);
+            }
+            if let Some(name) = name {
+                suite.set_attr("name", name);
+            }
+        }
+        MergeMode::Concatenate => {
+            let mut suites: Vec<XmlElement> = root
+                .elements()
+                .filter(|e| e.name == "suite")
+                .cloned()
+                .collect();
+            for other in rest.iter() {
+                suites.extend(other.elements().filter(|e| e.name == "suite").cloned());
+            }
+            let names: Vec<&str> = suites.iter().filter_map(|s| s.attr("name")).collect();
+            let mut top = XmlElement::new("suite");
+            top.set_attr("id", "s1");
+            top.set_attr("name", name.unwrap_or(&names.join(" & ")));
+            top.children = suites.into_iter().map(XmlNode::Element).collect();
+            let statuses: Vec<&XmlElement> =
+                top.elements().filter_map(|s| s.child("status")).collect();
+            let status = spanning_status(&statuses);
+            top.children.push(XmlNode::Element(status));
+            root.children
+                .retain(|c| !matches!(c, XmlNode::Element(e) if e.name == "suite"));
+            root.children.insert(0, XmlNode::Element(top));
+        }
+        MergeMode::Stitch => {
+            let suite = root
+                .child_mut("suite")
+                .ok_or(anyhow!("Output without suite"))?;
+            for other in rest.iter() {
+                let other_suite = other
+                    .child("suite")
+                    .ok_or(anyhow!("Output without suite"))?;
+                if other_suite.attr("name") != suite.attr("name") {
+                    return Err(anyhow!(
+                        "Cannot stitch outputs containing different root suites {:?} and {:?}",
+                        suite.attr("name"),
+                        other_suite.attr("name")
+                    ));
+                }
+                stitch_suite(suite, other_suite);
+            }
+            sort_suite(suite);
+            if let Some(name) = name {
+                suite.set_attr("name", name);
+            }
+        }
+    }
+
+    let suite = root
+        .child_mut("suite")
+        .ok_or(anyhow!("Output without suite"))?;
+    update_suite_status(suite);
+    renumber_suite(suite, "s1");
+    let statistics = statistics(suite);
+
+    root.children
+        .retain(|c| !matches!(c, XmlNode::Element(e) if e.name == "statistics"));
+    root.children.push(XmlNode::Element(statistics));
+    let mut errors_element = XmlElement::new("errors");
+    errors_element.children = errors;
+    root.children.push(XmlNode::Element(errors_element));
+    Ok(root)
+}
+
+/// Status of a suite that spans the execution of all given statuses,
+/// in the time format of the inputs
+fn spanning_status(statuses: &[&XmlElement]) -> XmlElement {
+    let mut status = XmlElement::new("status");
+    status.set_attr("status", "PASS");
+    let times = |key: &str| -> Vec<&str> {
+        statuses
+            .iter()
+            .filter_map(|s| s.attr(key))
+            .filter(|t| timestamp_seconds(t).is_some())
+            .collect()
+    };
+    // RF 7 writes start and elapsed, older versions starttime and endtime
+    let starts = times("start");
+    if let Some(start) = starts.iter().min_by_key(|t| normalize_timestamp(t)) {
+        let begin = timestamp_seconds(start).unwrap_or_default();
+        let end = statuses
+            .iter()
+            .filter_map(|s| {
+                let start = timestamp_seconds(s.attr("start")?)?;
+                Some(start + s.attr("elapsed")?.parse::<f64>().ok()?)
+            })
+            .fold(begin, f64::max);
+        status.set_attr("start", start);
+        status.set_attr("elapsed", &format!("{:.6}", end - begin));
+    }
+    let start = times("starttime")
+        .into_iter()
+        .min_by_key(|t| normalize_timestamp(t));
+    let end = times("endtime")
+        .into_iter()
+        .max_by_key(|t| normalize_timestamp(t));
+    if let (Some(start), Some(end)) = (start, end) {
+        status.set_attr("starttime", start);
+        status.set_attr("endtime", end);
+    }
+    status
+}
+
+/// Remove a child element and return its children
+fn take_children(element: &mut XmlElement, name: &str) -> Vec<XmlNode> {
+    let mut taken = Vec::new();
+    element.children.retain(|c| match c {
+        XmlNode::Element(e) if e.name == name => {
+            taken.extend(e.children.iter().cloned());
+            false
+        }
+        _ => true,
+    });
+    taken
+}
+
+/// Merge a suite of a later run into a suite of an earlier run
+fn merge_suite(old: &mut XmlElement, new: &XmlElement) {
+    for child in new.elements() {
+        match child.name.as_str() {
+            "suite" => {
+                let existing = old
+                    .elements_mut()
+                    .find(|e| e.name == "suite" && e.attr("name") == child.attr("name"));
+                match existing {
+                    Some(e) => merge_suite(e, child),
+                    None => {
+                        let mut added = child.clone();
+                        prefix_message(&mut added, "*HTML* Suite added from merged output.");
+                        insert_before_status(old, added);
+                    }
+                }
+            }
+            "test" => {
+                let existing = old
+                    .elements_mut()
+                    .find(|e| e.name == "test" && e.attr("name") == child.attr("name"));
+                match existing {
+                    // Like rebot a skipped rerun does not replace the earlier result
+                    Some(e) if child.status() == "SKIP" => {
+                        let message = skip_message(e, child);
+                        if let Some(status) = e.child_mut("status") {
+                            status.set_text(&message);
+                        }
+                    }
+                    Some(e) => {
+                        let message = merge_message(e, child);
+                        *e = child.clone();
+                        if let Some(status) = e.child_mut("status") {
+                            status.set_text(&message);
+                        }
+                    }
+                    None => {
+                        let mut added = child.clone();
+                        prefix_message(&mut added, "*HTML* Test added from merged output.");
+                        insert_before_status(old, added);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Add the suites and tests of a partial output that are missing in another one.
+/// Suite setups and teardowns are taken from the first output.
+fn stitch_suite(old: &mut XmlElement, new: &XmlElement) {
+    for child in new.elements() {
+        if child.name != "suite" && child.name != "test" {
+            continue;
+        }
+        let existing = old
+            .elements_mut()
+            .find(|e| e.name == child.name && e.attr("name") == child.attr("name"));
+        match existing {
+            Some(e) if child.name == "suite" => stitch_suite(e, child),
+            Some(_) => warn!("Test {:?} is part of multiple outputs", child.attr("name")),
+            None => insert_before_status(old, child.clone()),
+        }
+    }
+}
+
+/// Order child suites by their source and tests by their line
+fn sort_suite(suite: &mut XmlElement) {
+    let positions: Vec<usize> = suite
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| matches!(c, XmlNode::Element(e) if e.name == "suite" || e.name == "test"))
+        .map(|(i, _)| i)
+        .collect();
+    let mut sorted: Vec<XmlNode> = positions
+        .iter()
+        .map(|i| suite.children[*i].clone())
+        .collect();
+    sorted.sort_by_key(|c| match c {
+        XmlNode::Element(e) => (
+            e.attr("source").unwrap_or("").to_string(),
+            e.attr("line")
+                .and_then(|l| l.parse::<usize>().ok())
+                .unwrap_or(0),
+        ),
+        XmlNode::Text(_) => (String::new(), 0),
+    });
+    for (pos, child) in positions.into_iter().zip(sorted) {
+        suite.children[pos] = child;
+    }
+    for child in suite.elements_mut().filter(|e| e.name == "suite") {
+        sort_suite(child);
+    }
+}
+
+/// Message of a re-executed test, similar to the one rebot creates
+fn merge_message(old: &XmlElement, new: &XmlElement) -> String {
+    let status_span = |s: &str| format!("<span class=\"{}\">{}</span>", s.to_lowercase(), s);
+    let new_status = new.child("status");
+    let old_status = old.child("status");
+    let mut message = String::from("*HTML* Re-executed test has been merged.<hr>");
+    message.push_str(&format!("New status: {}<br>", status_span(new.status())));
+    let new_message = new_status.map(|s| s.text()).unwrap_or_default();
+    if !new_message.is_empty() {
+        message.push_str(&format!("New message: {}<hr>", strip_html(&new_message)));
+    } else {
+        message.push_str("<hr>");
+    }
+    message.push_str(&format!("Old status: {}", status_span(old.status())));
+    let old_message = old_status.map(|s| s.text()).unwrap_or_default();
+    if !old_message.is_empty() {
+        message.push_str(&format!("<br>Old message: {}", strip_html(&old_message)));
+    }
+    message
+}
+
+/// Message of a test whose rerun was skipped and ignored
+fn skip_message(old: &XmlElement, new: &XmlElement) -> String {
+    let text = |e: &XmlElement| e.child("status").map(|s| s.text()).unwrap_or_default();
+    let mut message = format!(
+        "*HTML* Test has been re-executed and results merged. \
+         Latter result had <span class=\"skip\">SKIP</span> status and was ignored. Message:\n{}",
+        strip_html(&text(new))
+    );
+    let old_message = text(old);
+    if !old_message.is_empty() {
+        message.push_str(&format!(
+            "<hr>Original message:\n{}",
+            strip_html(&old_message)
+        ));
+    }
+    message
+}
+
+fn strip_html(message: &str) -> String {
+    match message.strip_prefix("*HTML* ") {
+        Some(m) => m.to_string(),
+        None => escape(message).to_string(),
+    }
+}
+
+fn prefix_message(element: &mut XmlElement, prefix: &str) {
+    if let Some(status) = element.child_mut("status") {
+        let message = status.text();
+        if message.is_empty() {
+            status.set_text(prefix);
+        } else {
+            status.set_text(&format!("{}<hr>{}", prefix, strip_html(&message)));
+        }
+    }
+}
+
+/// Children are added before the status element of a suite to keep the schema order
+fn insert_before_status(suite: &mut XmlElement, child: XmlElement) {
+    let pos = suite
+        .children
+        .iter()
+        .position(|c| matches!(c, XmlNode::Element(e) if e.name == "status" || e.name == "kw" && e.attr("type") == Some("TEARDOWN")))
+        .unwrap_or(suite.children.len());
+    suite.children.insert(pos, XmlNode::Element(child));
+}
+
+/// Derive the status of a suite from its tests, suites and setup/teardown
+fn update_suite_status(suite: &mut XmlElement) -> String {
+    let mut statuses = Vec::new();
+    for child in suite.elements_mut() {
+        match child.name.as_str() {
+            "suite" => statuses.push(update_suite_status(child)),
+            "test" => statuses.push(child.status().to_string()),
+            "kw" if child.status() == "FAIL" => statuses.push("FAIL".to_string()),
+            _ => (),
+        }
+    }
+    let status = if statuses.iter().any(|s| s == "FAIL") {
+        "FAIL"
+    } else if statuses.is_empty() || statuses.iter().any(|s| s == "PASS") {
+        "PASS"
+    } else {
+        "SKIP"
+    };
+    suite.set_status(status);
+    status.to_string()
+}
+
+/// Give suites and tests new unique ids like `s1-s2-t3`
+fn renumber_suite(suite: &mut XmlElement, id: &str) {
+    suite.set_attr("id", id);
+    let mut suites = 0;
+    let mut tests = 0;
+    for child in suite.elements_mut() {
+        match child.name.as_str() {
+            "suite" => {
+                suites += 1;
+                renumber_suite(child, &format!("{id}-s{suites}"));
+            }
+            "test" => {
+                tests += 1;
+                child.set_attr("id", &format!("{id}-t{tests}"));
+            }
+            _ => (),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    pass: usize,
+    fail: usize,
+    skip: usize,
+}
+
+impl Counts {
+    fn add(&mut self, status: &str) {
+        match status {
+            "PASS" => self.pass += 1,
+            "FAIL" => self.fail += 1,
+            "SKIP" => self.skip += 1,
+            _ => (),
+        }
+    }
+
+    fn stat(&self, text: &str) -> XmlElement {
+        let mut stat = XmlElement::new("stat");
+        stat.set_attr("pass", &self.pass.to_string());
+        stat.set_attr("fail", &self.fail.to_string());
+        stat.set_attr("skip", &self.skip.to_string());
+        stat.set_text(text);
+        stat
+    }
+}
+
+/// Recalculate the statistics section from the suite tree
+fn statistics(suite: &XmlElement) -> XmlElement {
+    let mut total = Counts::default();
+    let mut tags: BTreeMap<String, Counts> = BTreeMap::new();
+    let mut suites = Vec::new();
+    suite_statistics(suite, "", &mut total, &mut tags, &mut suites);
+
+    let mut total_element = XmlElement::new("total");
+    total_element
+        .children
+        .push(XmlNode::Element(total.stat("All Tests")));
+    let mut tag_element = XmlElement::new("tag");
+    for (tag, counts) in tags.iter() {
+        tag_element
+            .children
+            .push(XmlNode::Element(counts.stat(tag)));
+    }
+    let mut suite_element = XmlElement::new("suite");
+    for stat in suites {
+        suite_element.children.push(XmlNode::Element(stat));
+    }
+
+    let mut statistics = XmlElement::new("statistics");
+    statistics.children = vec![
+        XmlNode::Element(total_element),
+        XmlNode::Element(tag_element),
+        XmlNode::Element(suite_element),
+    ];
+    statistics
+}
+
+fn suite_statistics(
+    suite: &XmlElement,
+    parent: &str,
+    total: &mut Counts,
+    tags: &mut BTreeMap<String, Counts>,
+    suites: &mut Vec<XmlElement>,
+) -> Counts {
+    let name = suite.attr("name").unwrap_or("");
+    let full_name = if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent}.{name}")
+    };
+    let pos = suites.len();
+    let mut counts = Counts::default();
+    for child in suite.elements() {
+        match child.name.as_str() {
+            "suite" => {
+                let c = suite_statistics(child, &full_name, total, tags, suites);
+                counts.pass += c.pass;
+                counts.fail += c.fail;
+                counts.skip += c.skip;
+            }
+            "test" => {
+                counts.add(child.status());
+                total.add(child.status());
+                for tag in child.elements().filter(|e| e.name == "tag") {
+                    tags.entry(tag.text()).or_default().add(child.status());
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut stat = counts.stat(&full_name);
+    stat.set_attr("id", suite.attr("id").unwrap_or(""));
+    stat.set_attr("name", name);
+    suites.insert(pos, stat);
+    counts
+}
+
+/// Merge output files and write the result as new output.xml
+pub fn merge_files(
+    xml_files: &[String],
+    output: &str,
+    mode: MergeMode,
+    name: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut outputs = Vec::new();
+    for xml_file in xml_files {
+        eprintln!("Reading {}", xml_file);
+        let xml = fs::read_to_string(xml_file).context(format!("File not found {}", xml_file))?;
+        outputs.push(read_xml(&xml).context(format!("Cannot read {}", xml_file))?);
+    }
+    let merged = merge_outputs(outputs, mode, name)?;
+    fs::write(output, write_xml(&merged)).context(format!("Cannot write {}", output))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(generated: &str, tests: &[(&str, &str)]) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <robot generator=\"Robot 7.1\" generated=\"{generated}\" rpa=\"false\" schemaversion=\"5\">\n\
+             <suite id=\"s1\" name=\"Demo\" source=\"/tmp/demo.robot\">\n"
+        );
+        for (index, (name, status)) in tests.iter().enumerate() {
+            xml.push_str(&format!(
+                "<test id=\"s1-t{}\" name=\"{name}\" line=\"2\">\n\
+                 <kw name=\"Log\" owner=\"BuiltIn\">\n<arg>a &amp; b</arg>\n\
+                 <status status=\"{status}\" start=\"{generated}\" elapsed=\"0.001\"/>\n</kw>\n\
+                 <tag>smoke</tag>\n\
+                 <status status=\"{status}\" start=\"{generated}\" elapsed=\"0.002\">msg {name}</status>\n\
+                 </test>\n",
+                index + 1
+            ));
+        }
+        xml.push_str(&format!(
+            "<status status=\"FAIL\" start=\"{generated}\" elapsed=\"0.003\"/>\n</suite>\n\
+             <statistics>\n</statistics>\n\
+             <errors>\n<msg time=\"{generated}\" level=\"WARN\">warning</msg>\n</errors>\n</robot>\n"
+        ));
+        xml
+    }
+
+    #[test]
+    fn roundtrip() -> anyhow::Result<()> {
+        let xml = output("2024-01-01T00:00:00", &[("A", "PASS")]);
+        let root = read_xml(&xml)?;
+        let written = write_xml(&root);
+        assert!(written.contains("<arg>a &amp; b</arg>\n"));
+        assert!(written.contains("elapsed=\"0.002\">msg A</status>\n</test>\n"));
+        assert_eq!(read_xml(&written)?, root);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_rerun() -> anyhow::Result<()> {
+        let first = read_xml(&output(
+            "2024-01-01T00:00:00",
+            &[("A", "PASS"), ("B", "FAIL")],
+        ))?;
+        let rerun = read_xml(&output(
+            "2024-01-01T01:00:00",
+            &[("B", "PASS"), ("C", "PASS")],
+        ))?;
+        let merged = merge_outputs(vec![first, rerun], MergeMode::Merge, None)?;
+
+        let suite = merged.child("suite").unwrap();
+        assert_eq!(suite.status(), "PASS");
+        let tests: Vec<&XmlElement> = suite.elements().filter(|e| e.name == "test").collect();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[1].attr("name"), Some("B"));
+        assert_eq!(tests[1].status(), "PASS");
+        assert!(tests[1]
+            .child("status")
+            .unwrap()
+            .text()
+            .starts_with("*HTML* Re-executed test has been merged."));
+        assert_eq!(tests[2].attr("id"), Some("s1-t3"));
+
+        let total = merged.child("statistics").unwrap().child("total").unwrap();
+        let stat = total.child("stat").unwrap();
+        assert_eq!(stat.attr("pass"), Some("3"));
+        assert_eq!(stat.attr("fail"), Some("0"));
+        assert_eq!(merged.child("errors").unwrap().elements().count(), 2);
+
+        // The result can be read again
+        assert_eq!(read_xml(&write_xml(&merged))?, merged);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_message_whitespace() -> anyhow::Result<()> {
+        let xml = output("2024-01-01T00:00:00", &[("A", "PASS")]).replace(
+            "<tag>smoke</tag>",
+            "<doc>  indented\n\nparagraph </doc>\n<msg level=\"INFO\"> </msg>",
+        );
+        let written = write_xml(&read_xml(&xml)?);
+        assert!(written.contains("<doc>  indented\n\nparagraph </doc>\n"));
+        assert!(written.contains("<msg level=\"INFO\"> </msg>\n"));
+        assert!(written.contains("<test id=\"s1-t1\" name=\"A\" line=\"2\">\n<kw"));
+        Ok(())
+    }
+
+    #[test]
+    fn skipped_rerun_keeps_result() -> anyhow::Result<()> {
+        let first = read_xml(&output("2024-01-01T00:00:00", &[("A", "FAIL")]))?;
+        let rerun = read_xml(&output("2024-01-01T01:00:00", &[("A", "SKIP")]))?;
+        let merged = merge_outputs(vec![first, rerun], MergeMode::Merge, None)?;
+        let test = merged.child("suite").unwrap().child("test").unwrap();
+        assert_eq!(test.status(), "FAIL");
+        let message = test.child("status").unwrap().text();
+        assert!(message.contains("Latter result had <span class=\"skip\">SKIP</span> status"));
+        assert!(message.ends_with("<hr>Original message:\nmsg A"));
+        Ok(())
+    }
+
+    #[test]
+    fn merge_different_suites_fails() -> anyhow::Result<()> {
+        let first = read_xml(&output("2024-01-01T00:00:00", &[("A", "PASS")]))?;
+        let mut other = first.clone();
+        other.child_mut("suite").unwrap().set_attr("name", "Other");
+        assert!(merge_outputs(vec![first, other], MergeMode::Merge, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn stitch_parallel_outputs() -> anyhow::Result<()> {
+        let partial = |suite: &str, test: &str, status: &str| {
+            format!(
+                "<robot generated=\"2024-01-01T00:00:00\" schemaversion=\"5\">\n\
+                 <suite id=\"s1\" name=\"Tests\" source=\"/t\">\n\
+                 <suite id=\"s1-s1\" name=\"{suite}\" source=\"/t/{suite}.robot\">\n\
+                 <test id=\"s1-s1-t1\" name=\"{test}\" line=\"3\">\n\
+                 <status status=\"{status}\" start=\"2024-01-01T00:00:00\" elapsed=\"1\"/>\n\
+                 </test>\n\
+                 <status status=\"{status}\" start=\"2024-01-01T00:00:00\" elapsed=\"1\"/>\n\
+                 </suite>\n\
+                 <status status=\"{status}\" start=\"2024-01-01T00:00:00\" elapsed=\"1\"/>\n\
+                 </suite>\n<statistics/>\n<errors/>\n</robot>\n"
+            )
+        };
+        let outputs = vec![
+            read_xml(&partial("B", "B1", "PASS"))?,
+            read_xml(&partial("A", "A1", "FAIL"))?,
+            read_xml(&partial("C", "C1", "PASS"))?,
+        ];
+        let stitched = merge_outputs(outputs, MergeMode::Stitch, None)?;
+        let top = stitched.child("suite").unwrap();
+        assert_eq!(top.status(), "FAIL");
+        let names: Vec<&str> = top
+            .elements()
+            .filter(|e| e.name == "suite")
+            .filter_map(|e| e.attr("name"))
+            .collect();
+        assert_eq!(names, vec!["A", "B", "C"]);
+        let ids: Vec<&str> = top
+            .elements()
+            .filter(|e| e.name == "suite")
+            .filter_map(|e| e.child("test").and_then(|t| t.attr("id")))
+            .collect();
+        assert_eq!(ids, vec!["s1-s1-t1", "s1-s2-t1", "s1-s3-t1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn concatenate() -> anyhow::Result<()> {
+        let first = read_xml(&output("2024-01-01T00:00:00", &[("A", "PASS")]))?;
+        let second = read_xml(&output("2024-01-01T01:00:00", &[("A", "FAIL")]))?;
+        let merged = merge_outputs(vec![first, second], MergeMode::Concatenate, None)?;
+
+        let top = merged.child("suite").unwrap();
+        assert_eq!(top.attr("name"), Some("Demo & Demo"));
+        assert_eq!(top.status(), "FAIL");
+        let status = top.child("status").unwrap();
+        assert_eq!(status.attr("start"), Some("2024-01-01T00:00:00"));
+        assert_eq!(status.attr("elapsed"), Some("3600.003000"));
+        let suites: Vec<&XmlElement> = top.elements().filter(|e| e.name == "suite").collect();
+        assert_eq!(suites.len(), 2);
+        assert_eq!(suites[0].status(), "PASS");
+        assert_eq!(suites[1].attr("id"), Some("s1-s2"));
+        assert_eq!(
+            suites[1].child("test").unwrap().attr("id"),
+            Some("s1-s2-t1")
+        );
+        let suite_stats = merged.child("statistics").unwrap().child("suite").unwrap();
+        let names: Vec<String> = suite_stats.elements().map(|e| e.text()).collect();
+        assert_eq!(
+            names,
+            vec!["Demo & Demo", "Demo & Demo.Demo", "Demo & Demo.Demo"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs;
+
+use crate::element::Element;
+use crate::timestamp::normalize_timestamp;
+
+/// Attributes of the robot root element and metadata of the top level suite
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunMetadata {
+    /// e.g. `Robot 7.1 (Python 3.12.3 on linux)`
+    pub generator: String,
+    /// Normalised generation time
+    pub generated: String,
+    pub rpa: bool,
+    pub schemaversion: String,
+    /// `<meta>` elements of the top level suite
+    pub suite_metadata: Vec<(String, String)>,
+}
+
+impl RunMetadata {
+    /// Robot Framework version from the generator like `7.1`
+    pub fn rf_version(&self) -> Option<&str> {
+        let mut words = self.generator.split_whitespace();
+        match words.next() {
+            Some("Robot") | Some("Rebot") => words.next(),
+            _ => None,
+        }
+    }
+
+    /// Date part of the generation time
+    pub fn date(&self) -> &str {
+        self.generated
+            .split_once('T')
+            .map(|(date, _)| date)
+            .unwrap_or(&self.generated)
+    }
+
+    /// Column header like `nightly-42 (2026-10-17, RF 7.1)`
+    pub fn column_label(&self, label: &str) -> String {
+        let mut details = Vec::new();
+        if !self.date().is_empty() {
+            details.push(self.date().to_string());
+        }
+        if let Some(version) = self.rf_version() {
+            details.push(format!("RF {version}"));
+        }
+        if details.is_empty() {
+            label.to_string()
+        } else {
+            format!("{} ({})", label, details.join(", "))
+        }
+    }
+
+    /// Take over an attribute of the robot root element
+    pub fn set_attribute(&mut self, key: &str, value: String) {
+        match key {
+            "generator" => self.generator = value,
+            "generated" => self.generated = normalize_timestamp(&value),
+            "rpa" => self.rpa = value == "true",
+            "schemaversion" => self.schemaversion = value,
+            _ => (),
+        }
+    }
+
+    /// Rows for the summary section: name and value
+    pub fn rows(&self) -> Vec<(String, String)> {
+        let mut rows = vec![
+            ("generator".to_string(), self.generator.clone()),
+            ("generated".to_string(), self.generated.clone()),
+            ("rpa".to_string(), self.rpa.to_string()),
+            ("schemaversion".to_string(), self.schemaversion.clone()),
+        ];
+        rows.extend(self.suite_metadata.iter().cloned());
+        rows.retain(|(_, value)| !value.is_empty());
+        rows
+    }
+}
+
+/// Split a `--label name=path` argument
+pub fn parse_label(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((label, path)) if !label.is_empty() && !path.is_empty() => {
+            Ok((label.to_string(), path.to_string()))
+        }
+        _ => Err(format!("Expected label=file but got {s}")),
+    }
+}
+
+/// Column headers of parsed input files, the user label replaces the file path
+/// of an input with the same path or naming the same file
+pub fn column_labels(
+    xml_files: &[String],
+    trees: &[Element],
+    labels: &[(String, String)],
+) -> Vec<String> {
+    xml_files
+        .iter()
+        .zip(trees.iter())
+        .map(|(xml_file, tree)| {
+            let label = labels
+                .iter()
+                .find(|(_, path)| same_file(path, xml_file))
+                .map(|(label, _)| label.as_str())
+                .unwrap_or(xml_file);
+            match &tree.summary {
+                Some(summary) => summary.metadata.column_label(label),
+                None => label.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn same_file(a: &str, b: &str) -> bool {
+    a == b
+        || matches!(
+            (fs::canonicalize(a), fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rf_parser::parse_tree;
+
+    #[test]
+    fn metadata_and_label() -> anyhow::Result<()> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<robot generator="Robot 7.1 (Python 3.12.3 on linux)" generated="2026-10-17T10:00:00.000000" rpa="false" schemaversion="5">
+<suite id="s1" name="Top">
+<suite id="s1-s1" name="Child">
+<meta name="Ignored">child</meta>
+<status status="PASS"/>
+</suite>
+<meta name="Version">1.2</meta>
+<meta name="Build">42</meta>
+<status status="PASS"/>
+</suite>
+</robot>"#;
+        let tree = parse_tree(xml)?;
+        let metadata = &tree.summary.as_ref().unwrap().metadata;
+        assert_eq!(metadata.rf_version(), Some("7.1"));
+        assert_eq!(metadata.date(), "2026-10-17");
+        assert_eq!(metadata.schemaversion, "5");
+        assert!(!metadata.rpa);
+        assert_eq!(
+            metadata.suite_metadata,
+            vec![
+                ("Version".to_string(), "1.2".to_string()),
+                ("Build".to_string(), "42".to_string())
+            ]
+        );
+        assert_eq!(
+            metadata.column_label("nightly-42"),
+            "nightly-42 (2026-10-17, RF 7.1)"
+        );
+        assert_eq!(RunMetadata::default().column_label("a.xml"), "a.xml");
+
+        assert_eq!(
+            parse_label("nightly-42=out/output.xml"),
+            Ok(("nightly-42".to_string(), "out/output.xml".to_string()))
+        );
+        assert!(parse_label("output.xml").is_err());
+        Ok(())
+    }
+}
@@ -1,11 +1,11 @@
 use csv::Writer;
 use std::cell::RefCell;
-use std::fs;
 use std::rc::Rc;
 
 use anyhow::anyhow;
 
 use crate::element::{ElementFlat, ResultType};
+use crate::summary::RunSummary;
 
 /// Multiple results merged together as matrix of flat elements.
 /// When the keyword is not executed it is None.
@@ -13,6 +13,16 @@ use crate::element::{ElementFlat, ResultType};
 pub struct MultiResultList {
     pub list: Rc<RefCell<Vec<Vec<Option<ElementFlat>>>>>,
     pub width: usize,
+    /// Statistics and execution errors of each input
+    pub summaries: Vec<Option<Rc<RunSummary>>>,
+    /// Do not expand rows that passed with the same subtree in all inputs
+    pub collapse_passing: bool,
+    /// Add a column with the failing keyword chain of failed tests to the CSV
+    pub failure_column: bool,
+    /// Add a column with the known issue of annotated tests to the CSV
+    pub issue_column: bool,
+    /// Tests that failed in the last input but passed before, without known issues
+    pub regressions: Vec<String>,
 }
 
 impl MultiResultList {
@@ -20,6 +30,11 @@ impl MultiResultList {
         MultiResultList {
             list: Rc::new(RefCell::new(Vec::new())),
             width,
+            summaries: vec![None; width],
+            collapse_passing: false,
+            failure_column: false,
+            issue_column: false,
+            regressions: Vec::new(),
         }
     }
     #[allow(dead_code)]
@@ -43,6 +58,13 @@ impl MultiResultList {
             record.push(format!("Type {result}"));
             record.push(format!("Name {result}"));
             record.push(format!("Result {result}"));
+            record.push(format!("Owner {result}"));
+            if self.failure_column {
+                record.push(format!("Failure {result}"));
+            }
+            if self.issue_column {
+                record.push(format!("Issue {result}"));
+            }
         }
         //println!("{record:?}");
         wtr.write_record(&record)?;
@@ -55,11 +77,25 @@ impl MultiResultList {
                         record.push(format!("{:?}", r.et));
                         record.push(r.name.to_string());
                         record.push(format!("{:?}", r.result));
+                        record.push(r.owner.to_string());
+                        if self.failure_column {
+                            record.push(r.failure.to_string());
+                        }
+                        if self.issue_column {
+                            record.push(r.issue.to_string());
+                        }
                     }
                     None => {
                         record.push("-".to_string());
                         record.push("-".to_string());
                         record.push("-".to_string());
+                        record.push("-".to_string());
+                        if self.failure_column {
+                            record.push("-".to_string());
+                        }
+                        if self.issue_column {
+                            record.push("-".to_string());
+                        }
                     }
                 }
             }
@@ -70,8 +106,44 @@ impl MultiResultList {
         wtr.flush()?;
         Ok(String::from_utf8(wtr.into_inner()?)?)
     }
+
+    /// Rows of the summary section of all inputs, prefixed by the input number,
+    /// followed by the regressions of the last input
+    pub fn summary_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for (input, summary) in self.summaries.iter().enumerate() {
+            if let Some(summary) = summary {
+                for row in summary.rows() {
+                    let mut record = vec![input.to_string()];
+                    record.extend(row);
+                    rows.push(record);
+                }
+            }
+        }
+        let last = self.width.saturating_sub(1).to_string();
+        for test in self.regressions.iter() {
+            let mut record = vec![last.clone(), "Regression".to_string(), test.clone()];
+            record.resize(SUMMARY_HEADER.len(), String::new());
+            rows.push(record);
+        }
+        rows
+    }
+
+    pub fn dump_summary_to_csv_str(&self) -> anyhow::Result<String> {
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.write_record(SUMMARY_HEADER)?;
+        for record in self.summary_rows() {
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        Ok(String::from_utf8(wtr.into_inner()?)?)
+    }
 }
 
+const SUMMARY_HEADER: [&str; 8] = [
+    "Input", "Section", "Name", "Pass", "Fail", "Skip", "Time", "Message",
+];
+
 #[cfg(feature = "odson")]
 use icu_locid::locale;
 
@@ -89,8 +161,6 @@ impl MultiResultList {
     /// there are many todos hidden here
     #[cfg(feature = "odson")]
     pub fn export_to_ods(&self) -> anyhow::Result<Vec<u8>> {
-        fs::create_dir_all("test_out").expect("create_dir");
-
         // let path = std::path::Path::new("test_out/lib_example.ods");
         // let mut wb = if path.exists() {
         // spreadsheet_ods::read_ods(path).unwrap()
@@ -117,6 +187,10 @@ impl MultiResultList {
         let mut notrun_style = CellStyle::new("notrun", &DefaultFormat::default());
         notrun_style.set_background_color(Rgb::new(0xd3, 0xd3, 0xd3));
         notrun_style.set_font_size(pt!(8));
+        // ffffa0 // lightyellow
+        let mut retry_style = CellStyle::new("retry", &DefaultFormat::default());
+        retry_style.set_background_color(Rgb::new(0xff, 0xff, 0xa0));
+        retry_style.set_font_size(pt!(8));
 
         // if wb.num_sheets() == 0 {
         let ref_no = wb.add_cellstyle(no_style);
@@ -124,13 +198,17 @@ impl MultiResultList {
         let ref_fail = wb.add_cellstyle(fail_style);
         let ref_skip = wb.add_cellstyle(skip_style);
         let ref_notrun = wb.add_cellstyle(notrun_style);
+        let ref_retry = wb.add_cellstyle(retry_style);
         let mut sheet = Sheet::new("Results");
-        let width = 4; // Amount of entries for each test analyzed testfile
+        let width = 7; // Amount of entries for each test analyzed testfile
         for result in 0..self.width {
             sheet.set_value(0, result as u32 * width + 0, format!("Type {result}"));
             sheet.set_value(0, result as u32 * width + 1, format!("Name {result}"));
             sheet.set_value(0, result as u32 * width + 2, format!("Result {result}"));
-            sheet.set_value(0, result as u32 * width + 3, format!("Depth {width}"));
+            sheet.set_value(0, result as u32 * width + 3, format!("Depth {result}"));
+            sheet.set_value(0, result as u32 * width + 4, format!("Owner {result}"));
+            sheet.set_value(0, result as u32 * width + 5, format!("Failure {result}"));
+            sheet.set_value(0, result as u32 * width + 6, format!("Issue {result}"));
         }
         let mut child_num = 0;
         for child in self.list.borrow().iter() {
@@ -149,31 +227,65 @@ impl MultiResultList {
                             format!("{:?}", r.result),
                         );
                         sheet.set_value(child_num, result as u32 * width + 3, r.depth.to_string());
+                        sheet.set_value(child_num, result as u32 * width + 4, r.owner.to_string());
+                        sheet.set_value(
+                            child_num,
+                            result as u32 * width + 5,
+                            r.failure.to_string(),
+                        );
+                        sheet.set_value(child_num, result as u32 * width + 6, r.issue.to_string());
                         let style = match r.result {
                             ResultType::Pass => &ref_pass,
                             ResultType::Fail => &ref_fail,
                             ResultType::NotRun => &ref_notrun,
                             ResultType::Skip => &ref_skip,
+                            ResultType::PassOnRetry => &ref_retry,
                             _ => &ref_no,
                         };
                         sheet.set_cellstyle(child_num, result as u32 * width + 0, style);
                         sheet.set_cellstyle(child_num, result as u32 * width + 1, style);
                         sheet.set_cellstyle(child_num, result as u32 * width + 2, style);
                         sheet.set_cellstyle(child_num, result as u32 * width + 3, style);
+                        sheet.set_cellstyle(child_num, result as u32 * width + 4, style);
+                        sheet.set_cellstyle(child_num, result as u32 * width + 5, style);
+                        sheet.set_cellstyle(child_num, result as u32 * width + 6, style);
                     }
                     None => {
                         sheet.set_value(child_num, result as u32 * width + 0, "-");
                         sheet.set_value(child_num, result as u32 * width + 1, "-");
                         sheet.set_value(child_num, result as u32 * width + 2, "-");
                         sheet.set_value(child_num, result as u32 * width + 3, "-");
+                        sheet.set_value(child_num, result as u32 * width + 4, "-");
+                        sheet.set_value(child_num, result as u32 * width + 5, "-");
+                        sheet.set_value(child_num, result as u32 * width + 6, "-");
                     }
                 }
             }
             child_num += 1;
         }
         wb.push_sheet(sheet);
+
+        let mut summary = Sheet::new("Summary");
+        for (col, title) in SUMMARY_HEADER.iter().enumerate() {
+            summary.set_value(0, col as u32, title.to_string());
+        }
+        for (row, record) in self.summary_rows().into_iter().enumerate() {
+            // Columns as in SUMMARY_HEADER, record[4] is the number of failures
+            let style = match record[1].as_str() {
+                "Metadata" => &ref_no,
+                "Regression" => &ref_fail,
+                "Error" if record[2] == "ERROR" => &ref_fail,
+                "Error" => &ref_retry,
+                _ if record[4] != "0" => &ref_fail,
+                _ => &ref_no,
+            };
+            for (col, value) in record.into_iter().enumerate() {
+                summary.set_value(row as u32 + 1, col as u32, value);
+                summary.set_cellstyle(row as u32 + 1, col as u32, style);
+            }
+        }
+        wb.push_sheet(summary);
         let buf: Vec<u8> = Vec::new();
-        spreadsheet_ods::write_ods(&mut wb, "export.ods").expect("write_ods");
         let result = spreadsheet_ods::write_ods_buf(&mut wb, buf)?;
         Ok(result)
     }
@@ -210,14 +322,17 @@ mod test_multi_result_list {
         mrl.push(vec![Some(ElementFlat {
             et: ElementType::Suite,
             result: ResultType::Pass,
-            name: "a suite".to_string(),
+            name: "a suite".into(),
             depth: 42,
+            owner: "".into(),
+            failure: String::new(),
+            issue: String::new(),
         })])?;
         println!("{:?}", mrl);
         let result = mrl.list.borrow();
 
         // TODO switch to assert_matches when stable
-        if let Some(_) = result[0][0] {
+        if result[0][0].is_some() {
         } else {
             panic!("Pattern does not match")
         }
@@ -232,8 +347,11 @@ mod test_multi_result_list {
             Some(ElementFlat {
                 et: ElementType::Suite,
                 result: ResultType::Pass,
-                name: "a suite".to_string(),
+                name: "a suite".into(),
                 depth: 10,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             }),
             None,
         ])?;
@@ -241,21 +359,27 @@ mod test_multi_result_list {
             Some(ElementFlat {
                 et: ElementType::Suite,
                 result: ResultType::Pass,
-                name: "a suite".to_string(),
+                name: "a suite".into(),
                 depth: 10,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             }),
             Some(ElementFlat {
                 et: ElementType::Keyword,
                 result: ResultType::Fail,
-                name: "another suite".to_string(),
+                name: "another suite".into(),
                 depth: 10,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             }),
         ])?;
         println!("{:?}", mrl);
         let result = mrl.list.borrow();
 
         // TODO switch to assert_matches when stable
-        if let Some(_) = result[0][0] {
+        if result[0][0].is_some() {
         } else {
             panic!("Pattern does not match")
         }
@@ -272,4 +396,49 @@ mod test_multi_result_list {
         println!("{}", mlrs.unwrap());
         Ok(())
     }
+
+    #[cfg(feature = "odson")]
+    #[test]
+    fn summary_styles() -> anyhow::Result<()> {
+        use crate::summary::{RunSummary, Stat};
+
+        let mut mrl = MultiResultList::new(1);
+        let stat = |name: &str, fail: u32, skip: u32| Stat {
+            name: name.to_string(),
+            pass: 1,
+            fail,
+            skip,
+        };
+        let mut summary = RunSummary {
+            tags: vec![stat("failed", 1, 0), stat("skipped", 0, 1)],
+            ..Default::default()
+        };
+        summary.metadata.generator = "Robot 7.2".to_string();
+        mrl.summaries[0] = Some(Rc::new(summary));
+        mrl.regressions = vec!["Suite.Test".to_string()];
+
+        let wb = spreadsheet_ods::read_ods_buf(&mrl.export_to_ods()?)?;
+        let sheet = wb.sheet(1);
+        let rows: Vec<(String, Option<String>)> = (1..=5)
+            .map(|row| {
+                let name = sheet.value(row, 2).as_str_or("").to_string();
+                (
+                    name,
+                    sheet.cellstyle(row, 2).map(|s| s.as_str().to_string()),
+                )
+            })
+            .collect();
+        let expect = [
+            ("generator", "no"),
+            ("rpa", "no"),
+            ("failed", "fail"),
+            ("skipped", "no"),
+            ("Suite.Test", "fail"),
+        ];
+        for ((name, style), (expect_name, expect_style)) in rows.iter().zip(expect) {
+            assert_eq!(name, expect_name);
+            assert_eq!(style.as_deref(), Some(expect_style));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+
+use crate::element::Element;
+use crate::merge::{merge_outputs, read_xml, write_xml, MergeMode};
+use crate::rf_parser::parse_tree;
+
+/// Find the partial `*/output.xml` files of a pabot run.
+/// The directory can be the `pabot_results` directory or the one containing it.
+pub fn find_pabot_outputs(dir: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = Path::new(dir);
+    let mut outputs = outputs_in(dir)?;
+    if outputs.is_empty() && dir.join("pabot_results").is_dir() {
+        outputs = outputs_in(&dir.join("pabot_results"))?;
+    }
+    if outputs.is_empty() {
+        return Err(anyhow!("No pabot outputs found in {}", dir.display()));
+    }
+    Ok(outputs)
+}
+
+fn outputs_in(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut outputs = Vec::new();
+    for entry in fs::read_dir(dir).context(format!("Cannot read {}", dir.display()))? {
+        let output = entry?.path().join("output.xml");
+        if output.is_file() {
+            outputs.push(output);
+        }
+    }
+    outputs.sort();
+    Ok(outputs)
+}
+
+/// Stitch the partial outputs of a pabot run into one output.xml string
+pub fn stitch_pabot_dir(dir: &str) -> anyhow::Result<String> {
+    let mut outputs = Vec::new();
+    for output in find_pabot_outputs(dir)? {
+        eprintln!("Parsing {}", output.display());
+        let xml =
+            fs::read_to_string(&output).context(format!("File not found {}", output.display()))?;
+        outputs.push(read_xml(&xml).context(format!("Cannot read {}", output.display()))?);
+    }
+    let stitched = merge_outputs(outputs, MergeMode::Stitch, None)?;
+    Ok(write_xml(&stitched))
+}
+
+/// Parse a pabot results directory into a single Element tree
+pub fn parse_pabot_dir(dir: &str) -> anyhow::Result<Element> {
+    parse_tree(&stitch_pabot_dir(dir)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::{ElementType, ResultType};
+
+    #[test]
+    fn stitch_fixture_dir() -> anyhow::Result<()> {
+        let dir = "robot/fixtures/pabot";
+        assert_eq!(find_pabot_outputs(dir)?.len(), 2);
+        assert!(find_pabot_outputs("robot/fixtures").is_err());
+
+        let tree = parse_pabot_dir(dir)?;
+        let top = tree.children.borrow()[0].clone();
+        assert_eq!(top.et, ElementType::Suite);
+        assert_eq!(top.result, ResultType::Fail);
+        // Ordered by suite source, not by the pabot process that ran the suite
+        let suites: Vec<(String, String, ResultType)> = top
+            .children
+            .borrow()
+            .iter()
+            .filter(|s| s.et == ElementType::Suite)
+            .map(|s| {
+                let test = s.children.borrow()[0].clone();
+                (
+                    s.name.to_string(),
+                    test.name.to_string(),
+                    test.result.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            suites,
+            vec![
+                ("A".to_string(), "A1".to_string(), ResultType::Fail),
+                ("B".to_string(), "B1".to_string(), ResultType::Pass),
+            ]
+        );
+        let summary = tree.summary.as_ref().unwrap();
+        assert_eq!((summary.total[0].pass, summary.total[0].fail), (1, 1));
+        Ok(())
+    }
+}
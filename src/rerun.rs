@@ -0,0 +1,210 @@
+use std::rc::Rc;
+
+use crate::element::{Element, ElementType, ResultType};
+use crate::filter::copy_element;
+
+/// A logical run made of an original output and the outputs of its reruns
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunGroup {
+    pub name: String,
+    pub files: Vec<String>,
+}
+
+/// Parse a group argument like `run1=a.xml,a_rerun.xml`
+pub fn parse_group(s: &str) -> Result<RunGroup, String> {
+    let (name, files) = s
+        .split_once('=')
+        .ok_or(format!("Expected name=file,file but got {s}"))?;
+    let files: Vec<String> = files
+        .split(',')
+        .filter(|f| !f.is_empty())
+        .map(|f| f.to_string())
+        .collect();
+    if files.is_empty() {
+        return Err(format!("No files in group {name}"));
+    }
+    Ok(RunGroup {
+        name: name.to_string(),
+        files,
+    })
+}
+
+/// Combine an original run and its reruns into one tree.
+/// Tests of later trees replace tests with the same name, a test that failed
+/// before and passes in a rerun gets the status PassOnRetry. Like rebot,
+/// a rerun that skipped or did not run a test keeps the earlier result.
+pub fn combine_reruns(trees: Vec<Element>) -> Option<Element> {
+    let mut trees = trees.into_iter();
+    let mut combined = trees.next()?;
+    for rerun in trees {
+        combined = combine(&combined, &rerun);
+    }
+    Some(combined)
+}
+
+fn combine(base: &Element, rerun: &Element) -> Element {
+    let mut element = copy_element(base);
+
+    let rerun_children = rerun.children.borrow();
+    let find = |child: &Element| {
+        rerun_children
+            .iter()
+            .find(|r| r.et == child.et && r.name == child.name)
+    };
+
+    for child in base.children.borrow().iter() {
+        let combined = match (&child.et, find(child)) {
+            (ElementType::Suite, Some(r)) => combine(child, r),
+            (ElementType::Test, Some(r))
+                if matches!(r.result, ResultType::Skip | ResultType::NotRun) =>
+            {
+                child.deep_clone()
+            }
+            (ElementType::Test, Some(r)) => {
+                let mut test = r.deep_clone();
+                if child.result == ResultType::Fail && r.result == ResultType::Pass {
+                    test.result = ResultType::PassOnRetry;
+                }
+                test
+            }
+            _ => child.deep_clone(),
+        };
+        element.children.borrow_mut().push(Rc::new(combined));
+    }
+
+    // Suites and tests that are only part of the rerun
+    for child in rerun_children.iter() {
+        let in_base = base
+            .children
+            .borrow()
+            .iter()
+            .any(|b| b.et == child.et && b.name == child.name);
+        if !in_base && matches!(child.et, ElementType::Suite | ElementType::Test) {
+            element
+                .children
+                .borrow_mut()
+                .push(Rc::new(child.deep_clone()));
+        }
+    }
+
+    if element.et == ElementType::Suite {
+        element.result = suite_result(&element);
+    }
+    element
+}
+
+/// Status of a suite after some of its tests were replaced by reruns
+fn suite_result(suite: &Element) -> ResultType {
+    let children = suite.children.borrow();
+    let results = children
+        .iter()
+        .filter(|c| matches!(c.et, ElementType::Suite | ElementType::Test))
+        .map(|c| &c.result);
+    let mut retried = false;
+    for result in results {
+        match result {
+            ResultType::Fail => return ResultType::Fail,
+            ResultType::PassOnRetry => retried = true,
+            _ => (),
+        }
+    }
+    if retried || suite.result == ResultType::Fail {
+        ResultType::PassOnRetry
+    } else {
+        suite.result.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::summary::RunSummary;
+
+    fn run(tests: &[(&str, ResultType)]) -> Element {
+        let root = Element::new(ElementType::Robot, String::new());
+        let mut suite = Element::new(ElementType::Suite, "Suite".to_string());
+        suite.result = if tests.iter().any(|(_, r)| *r == ResultType::Fail) {
+            ResultType::Fail
+        } else {
+            ResultType::Pass
+        };
+        for (name, result) in tests {
+            let mut test = Element::new(ElementType::Test, name.to_string());
+            test.result = result.clone();
+            suite.children.borrow_mut().push(Rc::new(test));
+        }
+        root.children.borrow_mut().push(Rc::new(suite));
+        root
+    }
+
+    fn results(tree: &Element) -> Vec<(String, ResultType)> {
+        let suite = tree.children.borrow()[0].clone();
+        let mut results = vec![(suite.name.to_string(), suite.result.clone())];
+        for test in suite.children.borrow().iter() {
+            results.push((test.name.to_string(), test.result.clone()));
+        }
+        results
+    }
+
+    #[test]
+    fn parse_group_argument() {
+        assert_eq!(
+            parse_group("run1=a.xml,a_rerun.xml"),
+            Ok(RunGroup {
+                name: "run1".to_string(),
+                files: vec!["a.xml".to_string(), "a_rerun.xml".to_string()],
+            })
+        );
+        assert!(parse_group("a.xml").is_err());
+        assert!(parse_group("run1=").is_err());
+    }
+
+    #[test]
+    fn passed_on_retry() {
+        let original = run(&[
+            ("A", ResultType::Pass),
+            ("B", ResultType::Fail),
+            ("C", ResultType::Fail),
+        ]);
+        let rerun = run(&[("B", ResultType::Pass), ("C", ResultType::Fail)]);
+        let combined = combine_reruns(vec![original, rerun]).unwrap();
+        assert_eq!(
+            results(&combined),
+            vec![
+                ("Suite".to_string(), ResultType::Fail),
+                ("A".to_string(), ResultType::Pass),
+                ("B".to_string(), ResultType::PassOnRetry),
+                ("C".to_string(), ResultType::Fail),
+            ]
+        );
+
+        let rerun = run(&[("C", ResultType::Pass)]);
+        let combined = combine_reruns(vec![combined, rerun]).unwrap();
+        assert_eq!(
+            results(&combined),
+            vec![
+                ("Suite".to_string(), ResultType::PassOnRetry),
+                ("A".to_string(), ResultType::Pass),
+                ("B".to_string(), ResultType::PassOnRetry),
+                ("C".to_string(), ResultType::PassOnRetry),
+            ]
+        );
+    }
+
+    #[test]
+    fn skipped_rerun_keeps_result() {
+        let mut original = run(&[("A", ResultType::Fail), ("B", ResultType::Pass)]);
+        original.summary = Some(Rc::new(RunSummary::default()));
+        let rerun = run(&[("A", ResultType::Skip), ("B", ResultType::NotRun)]);
+        let combined = combine_reruns(vec![original, rerun]).unwrap();
+        assert_eq!(
+            results(&combined),
+            vec![
+                ("Suite".to_string(), ResultType::Fail),
+                ("A".to_string(), ResultType::Fail),
+                ("B".to_string(), ResultType::Pass),
+            ]
+        );
+        assert!(combined.summary.is_some());
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::str;
+
+use anyhow::Context;
+
+use crate::element::Element;
+use crate::interner::Interner;
+use crate::metadata::RunMetadata;
+use crate::rf_parser::parse_tree_with_names;
+use crate::snapshot::{decode_snapshot, SNAPSHOT_MAGIC};
+
+/// A parsed output.xml or snapshot together with the label of its column
+#[derive(Debug)]
+pub struct ResultFile {
+    pub name: String,
+    pub tree: Element,
+}
+
+impl ResultFile {
+    /// Read an output file or snapshot, the path becomes the name
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::from_path_with(path, &mut Interner::default())
+    }
+
+    /// Same as `from_path` but names are shared with all files read through `names`
+    pub fn from_path_with(path: impl AsRef<Path>, names: &mut Interner) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path).context(format!("File not found {}", path.display()))?;
+        Self::from_bytes(&path.to_string_lossy(), &data, names)
+    }
+
+    /// Read an output file or snapshot, e.g. from a network stream or archive
+    pub fn from_reader(name: &str, reader: impl Read) -> anyhow::Result<Self> {
+        Self::from_reader_with(name, reader, &mut Interner::default())
+    }
+
+    /// Same as `from_reader` but names are shared with all files read through `names`
+    pub fn from_reader_with(
+        name: &str,
+        mut reader: impl Read,
+        names: &mut Interner,
+    ) -> anyhow::Result<Self> {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .context(format!("Cannot read {}", name))?;
+        Self::from_bytes(name, &data, names)
+    }
+
+    fn from_bytes(name: &str, data: &[u8], names: &mut Interner) -> anyhow::Result<Self> {
+        let tree = if data.starts_with(SNAPSHOT_MAGIC) {
+            decode_snapshot(data, names)
+        } else {
+            parse_tree_with_names(str::from_utf8(data)?, names)
+        }
+        .context(format!("Cannot parse {}", name))?;
+        Ok(ResultFile {
+            name: name.to_string(),
+            tree,
+        })
+    }
+
+    /// Column label instead of the path
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Generator, generation time and suite metadata of the run
+    pub fn metadata(&self) -> Option<&RunMetadata> {
+        self.tree.summary.as_ref().map(|s| &s.metadata)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_path_and_reader() -> anyhow::Result<()> {
+        let path = "robot/fixtures/rf7_output.xml";
+        let file = ResultFile::from_path(path)?;
+        assert_eq!(file.name, path);
+        assert_eq!(file.metadata().and_then(|m| m.rf_version()), Some("7.2"));
+
+        let xml = fs::read(path)?;
+        let read = ResultFile::from_reader("rf7", xml.as_slice())?;
+        assert_eq!(read.tree, file.tree);
+        assert_eq!(read.with_name("nightly").name, "nightly");
+
+        let error = ResultFile::from_reader("broken", &b"<robot><suite></test>"[..]).unwrap_err();
+        assert!(error.to_string().contains("broken"));
+        let unknown = String::from_utf8(xml.clone())?.replace("<doc>", "<unknown/><doc>");
+        let error = ResultFile::from_reader("unknown", unknown.as_bytes()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Unknown element <unknown/>"));
+        Ok(())
+    }
+
+    #[test]
+    fn shared_names() -> anyhow::Result<()> {
+        let mut names = Interner::default();
+        let first = ResultFile::from_path_with("robot/fixtures/rf7_output.xml", &mut names)?;
+        let xml = fs::read("robot/fixtures/rf7_output.xml")?;
+        let second = ResultFile::from_reader_with("again", xml.as_slice(), &mut names)?;
+        let suite = |f: &ResultFile| f.tree.children.borrow()[0].name.clone();
+        assert!(std::rc::Rc::ptr_eq(&suite(&first), &suite(&second)));
+        Ok(())
+    }
+}
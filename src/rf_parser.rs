@@ -1,16 +1,13 @@
 use std::any;
-use std::cell::Ref;
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::rc::Weak;
-use std::slice::Iter;
 use std::str;
 
 // use anyhow::Context;
 use colored::Colorize;
 use csv::Writer;
 
-use log::{debug, info, trace, warn};
+use log::{debug, trace, warn};
 use quick_xml::encoding::Decoder;
 use quick_xml::events::attributes;
 use quick_xml::events::attributes::{AttrError, Attribute};
@@ -18,7 +15,17 @@ use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 
 use crate::element::{Element, ElementFlat, ElementType, ResultList, ResultType};
+use crate::failures::failure_of;
+use crate::inputs::{create_output, STDIO};
+use crate::interner::Interner;
+use crate::metadata::RunMetadata;
 use crate::multi_result_list::MultiResultList;
+use crate::summary::parse_summary;
+use crate::timestamp::{elapsed_seconds, normalize_timestamp};
+
+/// Incremented whenever the parser makes a different tree from the same output,
+/// e.g. for a new element type or field
+pub const PARSER_REVISION: u64 = 1;
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -41,7 +48,10 @@ impl From<AttrError> for AppError {
 
 /// Convert Attribute to key and value
 /// TODO: Can we change the return type to e.g. back to Cow and &str?
-fn get_attribute_kv(decoder: Decoder, a: Result<Attribute, AttrError>) -> (String, String) {
+pub(crate) fn get_attribute_kv(
+    decoder: Decoder,
+    a: Result<Attribute, AttrError>,
+) -> (String, String) {
     let key = str::from_utf8(a.clone().unwrap().key.local_name().into_inner()).unwrap();
     let value;
     #[cfg(feature = "odson")]
@@ -79,24 +89,92 @@ fn get_attr_name<'a>(decoder: Decoder, name: &'a str, attr: attributes::Attribut
     //panic!("Cannot get attr {name}");
 }
 
+/// Library or resource of a keyword, RF 7 calls it `owner` and older versions `library`
+fn keyword_owner(decoder: Decoder, attr: attributes::Attributes) -> String {
+    let mut owner = String::new();
+    for a in attr {
+        let (key, value) = get_attribute_kv(decoder, a);
+        if key == "owner" || key == "library" {
+            owner = value;
+        }
+    }
+    owner
+}
+
 /// Convert a string status to a ResultType
 /// TODO This could belong to ResultType
-fn status_to_result(status: &str) -> ResultType {
+fn status_to_result(status: &str) -> anyhow::Result<ResultType> {
     match status {
-        "PASS" => ResultType::Pass,
-        "FAIL" => ResultType::Fail,
-        "NOT RUN" => ResultType::NotRun,
-        "SKIP" => ResultType::Skip,
-        s => {
-            info!("Panic:  \"{s}\"");
-            panic!("Panic:  \"{s}\"");
-            // ResultType::None
+        "PASS" => Ok(ResultType::Pass),
+        "FAIL" => Ok(ResultType::Fail),
+        "NOT RUN" => Ok(ResultType::NotRun),
+        "SKIP" => Ok(ResultType::Skip),
+        s => Err(anyhow::anyhow!("Unknown status \"{s}\"")),
+    }
+}
+
+/// Execution time of a status element, RF 7 writes `elapsed`
+/// and older versions `starttime` and `endtime`
+fn status_elapsed(decoder: Decoder, attr: attributes::Attributes) -> Option<f64> {
+    let mut elapsed = None;
+    let mut start = String::new();
+    let mut end = String::new();
+    for a in attr {
+        let (key, value) = get_attribute_kv(decoder, a);
+        match key.as_str() {
+            "elapsed" => elapsed = value.parse().ok(),
+            "starttime" => start = value,
+            "endtime" => end = value,
+            _ => (),
         }
     }
+    elapsed.or_else(|| elapsed_seconds(&start, &end))
+}
+
+/// ElementType of the XML elements that become part of the Element tree,
+/// all other elements only carry details of their parent.
+/// Keywords are distinguished by their `type` attribute.
+fn element_type(tag: &[u8], kw_type: &str) -> Option<ElementType> {
+    match tag {
+        b"suite" => Some(ElementType::Suite),
+        b"test" => Some(ElementType::Test),
+        b"kw" => match kw_type {
+            "SETUP" => Some(ElementType::Setup),
+            "TEARDOWN" => Some(ElementType::Teardown),
+            _ => Some(ElementType::Keyword),
+        },
+        b"if" => Some(ElementType::If),
+        b"branch" => Some(ElementType::Branch),
+        b"try" => Some(ElementType::Try),
+        b"for" => Some(ElementType::For),
+        b"iter" => Some(ElementType::Iter),
+        b"while" => Some(ElementType::While),
+        b"group" => Some(ElementType::Group),
+        b"return" => Some(ElementType::Return),
+        b"break" => Some(ElementType::Break),
+        b"continue" => Some(ElementType::Continue),
+        b"variable" => Some(ElementType::Var),
+        b"error" => Some(ElementType::Error),
+        _ => None,
+    }
 }
 
 pub struct ParserStats {
     pub max_depth: usize,
+    /// Symbol table for the names of the parsed elements
+    pub names: Interner,
+    /// Attributes of the robot element and metadata of the top level suite
+    pub metadata: RunMetadata,
+}
+
+impl ParserStats {
+    pub fn new(max_depth: usize) -> Self {
+        ParserStats {
+            max_depth,
+            names: Interner::default(),
+            metadata: RunMetadata::default(),
+        }
+    }
 }
 
 /// Slightly cursed recursive parser for output.xml files
@@ -111,10 +189,23 @@ pub fn parse_inner(
     if depth > stats.max_depth {
         stats.max_depth = depth;
     }
+    // Text inside of a status element is the failure message
+    let mut in_status = false;
+    // Text of a FAIL message is the failure message when the status has none
+    let mut in_fail_msg = false;
+    // Name of a `<meta>` element of the top level suite whose text follows
+    let mut meta: Option<String> = None;
+    let top_suite = depth == 1 && element.et == ElementType::Suite;
     loop {
         let ident = " ".repeat(depth * 4 + 4);
         match reader.read_event_into(&mut buf) {
-            Err(e) => panic!("Error at position {}: {:?}", reader.error_position(), e),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Error at position {}: {:?}",
+                    reader.error_position(),
+                    e
+                ))
+            }
 
             Ok(Event::Eof) => {
                 // println!("EOF");
@@ -129,19 +220,11 @@ pub fn parse_inner(
                 );
                 print_attributes(decoder, &ident, e.attributes());
                 let name = get_attr_name(decoder, "name", e.attributes());
-                let mut et: Option<ElementType> = None;
+                let kw_type = get_attr_name(decoder, "type", e.attributes());
+                let et = element_type(e.name().as_ref(), &kw_type);
                 match e.name().as_ref() {
+                    _ if et.is_some() => (),
                     b"robot" => (),
-                    b"suite" => et = Some(ElementType::Suite),
-                    b"test" => et = Some(ElementType::Test),
-                    b"kw" => et = Some(ElementType::Keyword),
-                    b"if" => et = Some(ElementType::If),
-                    b"branch" => et = Some(ElementType::Branch),
-                    b"try" => et = Some(ElementType::Try),
-                    b"for" => et = Some(ElementType::For),
-                    b"iter" => et = Some(ElementType::Iter),
-                    b"while" => et = Some(ElementType::While),
-                    b"continue" => et = Some(ElementType::Continue),
                     b"doc" => (),
                     b"arg" => (),
                     b"statistics" => break,
@@ -151,31 +234,43 @@ pub fn parse_inner(
                     b"tag" => (),
                     b"msg" => (),
                     b"var" => (),
-                    b"return" => (),
                     b"value" => (),
-                    b"break" => (),
                     b"status" => (),
-                    // At least in one example a "pattern" appeared here instead of at End
-                    b"pattern" => break,
+                    b"pattern" => (),
+                    b"meta" => (),
+                    b"timeout" => (),
                     s => {
-                        warn!("Unmatched {:?}", str::from_utf8(s).unwrap());
-                        panic!()
+                        return Err(anyhow::anyhow!(
+                            "Unknown element <{}> at position {}",
+                            String::from_utf8_lossy(s),
+                            reader.buffer_position()
+                        ))
                     }
                 }
+                if e.name().as_ref() == b"robot" && depth == 0 {
+                    for a in e.attributes() {
+                        let (key, value) = get_attribute_kv(decoder, a);
+                        stats.metadata.set_attribute(&key, value);
+                    }
+                }
+                if e.name().as_ref() == b"msg" {
+                    in_fail_msg = get_attr_name(decoder, "level", e.attributes()) == "FAIL";
+                }
+                if e.name().as_ref() == b"meta" && top_suite {
+                    meta = Some(name.clone());
+                }
                 if e.name().as_ref() == b"status" {
                     let status = get_attr_name(decoder, "status", e.attributes());
                     debug!("{ident}Got status from Start Element {:?}", status);
-                    element.result = status_to_result(&status);
+                    element.result = status_to_result(&status)?;
+                    element.elapsed = status_elapsed(decoder, e.attributes());
+                    in_status = true;
                 }
 
-                if let Some(e) = et {
-                    let mut suite_element = Element {
-                        et: e,
-                        children: RefCell::new(Vec::new()),
-                        parent: RefCell::new(Weak::new()),
-                        result: ResultType::None,
-                        name,
-                    };
+                if let Some(et) = et {
+                    let mut suite_element = Element::new(et, stats.names.intern(&name));
+                    suite_element.owner =
+                        stats.names.intern(&keyword_owner(decoder, e.attributes()));
                     parse_inner(reader, &mut suite_element, depth + 1, stats)?;
                     let mut parent = element.parent.borrow_mut();
                     let rc_suite_element = Rc::new(suite_element);
@@ -187,7 +282,13 @@ pub fn parse_inner(
                 //println!("{ident}Text {}", any::type_name_of_val(&e));
                 let text: &str = &e.unescape().unwrap();
                 let len = usize::min(text.len(), 30);
-                debug!("{ident}    Text: {} ...", text.get(0..len).unwrap());
+                debug!("{ident}    Text: {} ...", text.get(0..len).unwrap_or(text));
+                if in_status || (in_fail_msg && element.message.is_empty()) {
+                    element.message = text.to_string();
+                }
+                if let Some(name) = meta.take() {
+                    stats.metadata.suite_metadata.push((name, text.to_string()));
+                }
             }
             Ok(Event::End(e)) => {
                 // End means elements that end without having sub elements
@@ -200,17 +301,15 @@ pub fn parse_inner(
                 );
 
                 match e.name().as_ref() {
+                    b"status" => in_status = false,
+                    b"msg" => in_fail_msg = false,
+                    b"meta" => {
+                        if let Some(name) = meta.take() {
+                            stats.metadata.suite_metadata.push((name, String::new()));
+                        }
+                    }
                     b"robot" => break,
-                    b"suite" => break,
-                    b"test" => break,
-                    b"kw" => break,
-                    b"branch" => break,
-                    b"if" => break,
-                    b"try" => break,
-                    b"for" => break,
-                    b"iter" => break,
-                    b"while" => break,
-                    b"pattern" => break,
+                    s if element_type(s, "").is_some() => break,
                     _ => (),
                 }
             }
@@ -223,20 +322,24 @@ pub fn parse_inner(
                 );
 
                 match e.name().as_ref() {
-                    b"timeout" => continue,
                     b"status" => (),
-                    b"var" => (),
-                    s => panic!("Cannot parse {}", str::from_utf8(s).unwrap()),
+                    b"timeout" | b"var" | b"value" | b"arg" | b"msg" | b"doc" | b"tag"
+                    | b"pattern" | b"meta" => continue,
+                    s => {
+                        return Err(anyhow::anyhow!(
+                            "Unknown element <{}/> at position {}",
+                            String::from_utf8_lossy(s),
+                            reader.buffer_position()
+                        ))
+                    }
                 }
 
                 print_attributes(decoder, &ident, e.attributes());
-                match element.et {
-                    ElementType::Keyword | ElementType::Suite | ElementType::Test => {
-                        let status = get_attr_name(decoder, "status", e.attributes());
-                        debug!("{ident}Got status from Empty element {:?}", status);
-                        element.result = status_to_result(&status);
-                    }
-                    _ => (),
+                if element.et != ElementType::Robot {
+                    let status = get_attr_name(decoder, "status", e.attributes());
+                    debug!("{ident}Got status from Empty element {:?}", status);
+                    element.result = status_to_result(&status)?;
+                    element.elapsed = status_elapsed(decoder, e.attributes());
                 }
             }
             Ok(Event::Decl(e)) => {
@@ -256,6 +359,61 @@ pub fn parse_inner(
     Ok(())
 }
 
+/// Return the attributes of the robot root element like `generator` and `generated`
+pub fn robot_attributes(xml_data: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut reader = Reader::from_str(xml_data);
+    let decoder = reader.decoder();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.name().as_ref() == b"robot" => {
+                return Ok(e
+                    .attributes()
+                    .map(|a| get_attribute_kv(decoder, a))
+                    .collect());
+            }
+            Event::Eof => return Err(anyhow::anyhow!("No robot element found")),
+            _ => (),
+        }
+    }
+}
+
+/// Return the normalised generation time of an output file
+pub fn generated_timestamp(xml_data: &str) -> anyhow::Result<String> {
+    let generated = robot_attributes(xml_data)?
+        .into_iter()
+        .find(|(k, _)| k == "generated")
+        .map(|(_, v)| v)
+        .unwrap_or_default();
+    Ok(normalize_timestamp(&generated))
+}
+
+/// Parse a XML str into an Element tree
+pub fn parse_tree(xml_data: &str) -> anyhow::Result<Element> {
+    parse_tree_with_names(xml_data, &mut Interner::default())
+}
+
+/// Parse a XML str into an Element tree sharing the names of earlier parsed trees
+pub fn parse_tree_with_names(xml_data: &str, names: &mut Interner) -> anyhow::Result<Element> {
+    let mut reader = Reader::from_str(xml_data);
+    reader.config_mut().trim_text(true);
+
+    let mut root_element = Element::new(ElementType::Robot, "");
+    let mut stats = ParserStats {
+        max_depth: 1,
+        names: std::mem::take(names),
+        metadata: RunMetadata::default(),
+    };
+
+    let parsed = parse_inner(&mut reader, &mut root_element, 0, &mut stats);
+    *names = stats.names;
+    parsed?;
+    let mut summary = parse_summary(&mut reader)?;
+    summary.metadata = stats.metadata;
+    root_element.summary = Some(Rc::new(summary));
+    debug!("Maximum tree depth {}", stats.max_depth);
+    Ok(root_element)
+}
+
 /// Should iterate over multiple trees of Elements to compare
 /// We are getting N trees and we want to compare each of the child elements
 /// This is similar to a generic N-times-zip function
@@ -272,42 +430,12 @@ pub fn diff_tree(
         return Ok(());
     }
 
-    let len = elements.len();
-
-    // temporary values to store our borrowed children Vec
-    let mut borrowed_children: Vec<Option<Ref<'_, Vec<Rc<Element>>>>> = Vec::new();
-
-    for element in elements.iter() {
-        let children = match element {
-            Some(s) => Some(s.children.borrow()),
-            None => None,
-        };
-        borrowed_children.push(children);
-    }
-
-    let mut iterators: Vec<Option<Iter<Rc<Element>>>> = Vec::new();
-
-    for child in borrowed_children.iter() {
-        let iterator = match child {
-            Some(s) => Some(s.iter()),
-            None => None,
-        };
-        iterators.push(iterator);
-    }
-
-    loop {
+    for row in rows_by_position(elements) {
+        let velem: Vec<Option<&Element>> = row.iter().map(|e| e.as_deref()).collect();
         let mut elf: Vec<Option<ElementFlat>> = Vec::new();
-
-        let mut count = 0;
-        let mut velem: Vec<Option<&Element>> = Vec::new();
         let mut state: String = String::new();
-        for iterator in iterators.iter_mut() {
-            let next: Option<&Rc<Element>> = match iterator {
-                Some(ref mut s) => s.next(),
-                None => None,
-            };
-
-            match next {
+        for (count, element) in velem.iter().enumerate() {
+            match element {
                 Some(s) => {
                     trace!(
                         "name: {}-{} {:?} {:?} {:?}",
@@ -317,32 +445,20 @@ pub fn diff_tree(
                         s.et,
                         s.result
                     );
-                    elf.push(Some(ElementFlat {
-                        et: s.et.clone(),
-                        result: s.result.clone(),
-                        name: s.name.clone(),
-                        depth,
-                    }));
+                    elf.push(Some(flat_element(s, depth)));
                     state.push_str(&format!(
                         "{:<16} {:<16?} {:<16} ",
                         s.name.blue(),
                         s.et,
                         s.result.to_string().yellow()
                     ));
-                    velem.push(Some(s));
                 }
                 None => {
                     trace!("name: {}-{} None", count, depth);
                     elf.push(None);
                     state.push_str(&format!("{:<16} {:<16?} {:<16}", "-", "-", "-"));
-                    velem.push(None);
                 }
             }
-            count += 1;
-        }
-
-        if velem.iter().filter(|s| s.is_none()).count() == len {
-            break;
         }
 
         {
@@ -353,27 +469,96 @@ pub fn diff_tree(
         if !quiet {
             println!("{}", state);
         }
-        diff_tree(&velem, &mrl, depth + 1, max_depth, quiet)?;
+        if mrl.collapse_passing && same_passing_subtree(&velem) {
+            continue;
+        }
+        diff_tree(&velem, mrl, depth + 1, max_depth, quiet)?;
     }
     Ok(())
 }
 
-/// Parse a XML str and dump it into a CSV file
+/// Rows of the children of multiple trees matched by position, the n-th
+/// child of all trees forms the n-th row. Shorter trees are padded with None.
+pub(crate) fn rows_by_position(elements: &[Option<&Element>]) -> Vec<Vec<Option<Rc<Element>>>> {
+    let children: Vec<Vec<Rc<Element>>> = elements
+        .iter()
+        .map(|e| match e {
+            Some(s) => s.children.borrow().clone(),
+            None => Vec::new(),
+        })
+        .collect();
+    let len = children.iter().map(|c| c.len()).max().unwrap_or(0);
+    (0..len)
+        .map(|index| children.iter().map(|c| c.get(index).cloned()).collect())
+        .collect()
+}
+
+/// Row of a flat list for an element
+pub(crate) fn flat_element(element: &Element, depth: usize) -> ElementFlat {
+    ElementFlat {
+        et: element.et.clone(),
+        result: element.result.clone(),
+        name: element.name.clone(),
+        depth,
+        owner: element.owner.clone(),
+        failure: flat_failure(element),
+        issue: flat_issue(element),
+    }
+}
+
+/// Failing keyword chain of a failed test, empty for everything else
+fn flat_failure(element: &Element) -> String {
+    if element.et == ElementType::Test && element.result == ResultType::Fail {
+        failure_of(element, "").to_string()
+    } else {
+        String::new()
+    }
+}
+
+fn flat_issue(element: &Element) -> String {
+    element
+        .issue
+        .as_ref()
+        .map(|issue| issue.to_string())
+        .unwrap_or_default()
+}
+
+/// True when all inputs have the element, it passed and the whole subtree
+/// is the same in all of them
+pub(crate) fn same_passing_subtree(elements: &[Option<&Element>]) -> bool {
+    let Some(Some(first)) = elements.first() else {
+        return false;
+    };
+    first.result == ResultType::Pass
+        && elements
+            .iter()
+            .all(|e| e.is_some_and(|e| same_subtree(first, e)))
+}
+
+fn same_subtree(a: &Element, b: &Element) -> bool {
+    let (a_children, b_children) = (a.children.borrow(), b.children.borrow());
+    a.et == b.et
+        && a.name == b.name
+        && a.result == b.result
+        && a_children.len() == b_children.len()
+        && a_children
+            .iter()
+            .zip(b_children.iter())
+            .all(|(a, b)| same_subtree(a, b))
+}
+
+/// Parse a XML str and dump it into a CSV file, `-` writes to stdout.
+/// The statistics go to stderr then to keep stdout clean.
 pub fn parse(xml_data: &str, csv_file: &str) -> anyhow::Result<ResultList> {
     let mut reader = Reader::from_str(xml_data);
     reader.config_mut().trim_text(true);
 
     let depth = 0;
-    let mut root_element: Element = Element {
-        et: ElementType::Robot,
-        children: RefCell::new(Vec::new()),
-        parent: RefCell::new(Weak::new()),
-        result: ResultType::None,
-        name: String::new(),
-    };
-    let mut stats = ParserStats { max_depth: 3 };
+    let mut root_element = Element::new(ElementType::Robot, String::new());
+    let mut stats = ParserStats::new(3);
 
     parse_inner(&mut reader, &mut root_element, depth, &mut stats)?;
+    let summary = parse_summary(&mut reader)?;
 
     // println!("Root {:#?}", root_element);
     // println!("{:?}", current);
@@ -387,8 +572,29 @@ pub fn parse(xml_data: &str, csv_file: &str) -> anyhow::Result<ResultList> {
         println!("{result:?}")
     }*/
     dump_csv_file(csv_file, &results)?;
-    println!("Parsed {} elements", results.list.borrow().len());
-    println!("Maximum tree depth {}", stats.max_depth);
+    let mut info = vec![
+        format!("Parsed {} elements", results.list.borrow().len()),
+        format!("Maximum tree depth {}", stats.max_depth),
+    ];
+    for stat in summary.total.iter() {
+        info.push(format!(
+            "{}: {} passed, {} failed, {} skipped",
+            stat.name, stat.pass, stat.fail, stat.skip
+        ));
+    }
+    for error in summary.errors.iter() {
+        info.push(format!(
+            "{} {} {}",
+            error.timestamp, error.level, error.text
+        ));
+    }
+    for line in info {
+        if csv_file == STDIO {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
     Ok(results)
 }
 
@@ -400,14 +606,8 @@ pub fn parse_from_str_to_str(xml_data: &str) -> anyhow::Result<String> {
     reader.config_mut().trim_text(true);
 
     let depth = 0;
-    let mut root_element: Element = Element {
-        et: ElementType::Robot,
-        children: RefCell::new(Vec::new()),
-        parent: RefCell::new(Weak::new()),
-        result: ResultType::None,
-        name: String::new(),
-    };
-    let mut stats = ParserStats { max_depth: 0 };
+    let mut root_element = Element::new(ElementType::Robot, String::new());
+    let mut stats = ParserStats::new(0);
 
     parse_inner(&mut reader, &mut root_element, depth, &mut stats)?;
 
@@ -426,10 +626,14 @@ pub fn parse_from_str_to_str(xml_data: &str) -> anyhow::Result<String> {
     dump_csv_to_str(&results)
 }
 
-/// Dump a ResultList into a single CSV file
+/// Dump a ResultList into a single CSV file, `-` writes to stdout
 pub fn dump_csv_file(csv_file: &str, results: &ResultList) -> anyhow::Result<()> {
-    //let mut wtr = csv::Writer::from_writer(io::stdout());
-    let mut wtr = csv::Writer::from_path(csv_file)?;
+    dump_csv(create_output(csv_file)?, results)
+}
+
+/// Dump a ResultList as CSV into a writer
+pub fn dump_csv<W: std::io::Write>(writer: W, results: &ResultList) -> anyhow::Result<()> {
+    let mut wtr = Writer::from_writer(writer);
 
     wtr.write_record(["Type", "Name", "Result"])?;
     for child in results.list.borrow().iter() {
@@ -445,32 +649,17 @@ pub fn dump_csv_file(csv_file: &str, results: &ResultList) -> anyhow::Result<()>
 }
 
 /// Dump a ResultList into a single CSV String
-/// TODO combine with above
 pub fn dump_csv_to_str(results: &ResultList) -> anyhow::Result<String> {
-    let mut wtr = Writer::from_writer(vec![]);
-    wtr.write_record(["Type", "Name", "Result"])?;
-    for child in results.list.borrow().iter() {
-        wtr.write_record(&[
-            format!("{:?}", child.et),
-            child.name.to_string(),
-            format!("{:?}", child.result),
-        ])?;
-    }
-
-    wtr.flush()?;
-    Ok(String::from_utf8(wtr.into_inner()?)?)
+    let mut buffer = Vec::new();
+    dump_csv(&mut buffer, results)?;
+    Ok(String::from_utf8(buffer)?)
 }
 
 /// Dump an Element tree into a flat ResultList
 pub fn dump_flat(element: &Element, results: &mut ResultList) {
     debug!("Flat Dump:");
     //println!("{:?}; {}", element.et, element.name);
-    results.list.borrow_mut().push(ElementFlat {
-        et: element.et.clone(),
-        name: element.name.clone(),
-        result: element.result.clone(),
-        depth: 0,
-    });
+    results.list.borrow_mut().push(flat_element(element, 0));
     dump_flat_inner(element, results, 1);
 }
 
@@ -478,12 +667,7 @@ pub fn dump_flat(element: &Element, results: &mut ResultList) {
 fn dump_flat_inner(element: &Element, results: &mut ResultList, depth: usize) {
     for child in element.children.borrow().iter() {
         debug!("{:?}; {}; {:?}", child.et, child.name, child.result);
-        results.list.borrow_mut().push(ElementFlat {
-            et: child.et.clone(),
-            name: child.name.clone(),
-            result: child.result.clone(),
-            depth,
-        });
+        results.list.borrow_mut().push(flat_element(child, depth));
         dump_flat_inner(child, results, depth + 1);
     }
 }
@@ -0,0 +1,451 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use log::warn;
+use serde_json::{json, Value};
+
+use crate::blend_results::blend_trees;
+use crate::element::{Element, ResultType};
+use crate::export::{Exporter, JsonExporter};
+use crate::multi_result_list::MultiResultList;
+use crate::trend::{escape_html, run_infos, TrendReport};
+
+/// Answer to a request
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Self {
+        Response {
+            status: "200 OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn not_found() -> Self {
+        Response {
+            status: "404 Not Found",
+            content_type: "text/plain",
+            body: "Not found".to_string(),
+        }
+    }
+
+    fn bad_request(message: String) -> Self {
+        Response {
+            status: "400 Bad Request",
+            content_type: "text/plain",
+            body: message,
+        }
+    }
+}
+
+/// Decode `%20` escapes and `+` of a query string value
+pub fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(b) => {
+                        decoded.push(b);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Split a request target into the path and its query parameters
+pub fn parse_target(target: &str) -> (String, Vec<(String, String)>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (key, value) = p.split_once('=').unwrap_or((p, ""));
+            (url_decode(key), url_decode(value))
+        })
+        .collect();
+    (path.to_string(), params)
+}
+
+fn result_class(result: &ResultType) -> &'static str {
+    match result {
+        ResultType::Pass => "P",
+        ResultType::Fail => "F",
+        ResultType::Skip => "S",
+        ResultType::NotRun => "N",
+        ResultType::PassOnRetry => "R",
+        _ => "",
+    }
+}
+
+/// Time a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line and headers a client may send
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// A request target and where to send its response
+type Request = (String, Sender<Response>);
+
+/// Parsed runs served on a local port
+pub struct Server {
+    pub names: Vec<String>,
+    pub trees: Vec<Element>,
+    pub depth: usize,
+    /// Trend of all runs, which do not change while serving
+    trend: TrendReport,
+}
+
+impl Server {
+    pub fn new(names: Vec<String>, trees: Vec<Element>, depth: usize) -> Self {
+        let trend = TrendReport::new(run_infos(&names, &trees), &trees);
+        Server {
+            names,
+            trees,
+            depth,
+            trend,
+        }
+    }
+
+    /// Runs selected with `run=<index>` parameters, all runs without any
+    fn selected_runs(&self, params: &[(String, String)]) -> Result<Vec<usize>, String> {
+        let mut runs = Vec::new();
+        for (key, value) in params.iter().filter(|(k, _)| k == "run") {
+            match value.parse::<usize>() {
+                Ok(run) if run < self.trees.len() => runs.push(run),
+                _ => return Err(format!("Unknown {key} {value}")),
+            }
+        }
+        if runs.is_empty() {
+            runs = (0..self.trees.len()).collect();
+        }
+        Ok(runs)
+    }
+
+    fn depth(&self, params: &[(String, String)]) -> Result<usize, String> {
+        match params.iter().find(|(k, _)| k == "depth") {
+            Some((_, value)) if value.is_empty() => Ok(self.depth),
+            Some((_, value)) => value.parse().map_err(|_| format!("Bad depth {value}")),
+            None => Ok(self.depth),
+        }
+    }
+
+    fn blend(&self, runs: &[usize], depth: usize) -> anyhow::Result<MultiResultList> {
+        let trees: Vec<Element> = runs.iter().map(|r| self.trees[*r].deep_clone()).collect();
+        let names: Vec<String> = runs.iter().map(|r| self.names[*r].clone()).collect();
+        blend_trees(&trees, &names, depth, false)
+    }
+
+    /// Answer a GET request for a target like `/api/blend?run=0&run=2`
+    pub fn handle(&self, target: &str) -> anyhow::Result<Response> {
+        let (path, params) = parse_target(target);
+        let runs = match self.selected_runs(&params) {
+            Ok(runs) => runs,
+            Err(message) => return Ok(Response::bad_request(message)),
+        };
+        let depth = match self.depth(&params) {
+            Ok(depth) => depth,
+            Err(message) => return Ok(Response::bad_request(message)),
+        };
+        let response = match path.as_str() {
+            "/" => Response::ok("text/html; charset=utf-8", self.index_html(&runs, depth)?),
+            "/trend" => Response::ok("text/html; charset=utf-8", self.trend.to_html()),
+            "/api/runs" => Response::ok("application/json", json!(self.names).to_string()),
+            "/api/blend" => {
+                let mrl = self.blend(&runs, depth)?;
+                let json = JsonExporter.export_to_vec(&mrl)?;
+                Response::ok("application/json", String::from_utf8(json)?)
+            }
+            "/api/history" => {
+                let report = &self.trend;
+                let test = params.iter().find(|(k, _)| k == "test").map(|(_, v)| v);
+                let tests: Vec<Value> = report
+                    .tests
+                    .iter()
+                    .filter(|t| test.is_none_or(|name| &t.path == name))
+                    .map(|t| {
+                        json!({
+                            "test": t.path,
+                            "status": t.status_string(),
+                            "failure_rate": t.failure_rate(),
+                            "results": t.results.iter()
+                                .map(|r| r.as_ref().map(|r| r.to_string()))
+                                .collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect();
+                let runs: Vec<Value> = report
+                    .runs
+                    .iter()
+                    .map(|r| json!({ "label": r.label, "date": r.date }))
+                    .collect();
+                Response::ok(
+                    "application/json",
+                    json!({ "runs": runs, "tests": tests }).to_string(),
+                )
+            }
+            _ => Response::not_found(),
+        };
+        Ok(response)
+    }
+
+    /// Form to select the runs and the blend of the selected runs
+    fn index_html(&self, runs: &[usize], depth: usize) -> anyhow::Result<String> {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Blend</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; font-size: 10pt; }\n\
+             td, th { padding: 1px 8px; text-align: left; }\n\
+             .P { background: #90ee90; } .F { background: #ffbcb8; }\n\
+             .S { background: #add8e6; } .N { background: #d3d3d3; }\n\
+             .R { background: #ffffa0; }\n\
+             </style>\n</head>\n<body>\n<h1>Blend</h1>\n<form method=\"get\" action=\"/\">\n",
+        );
+        for (index, name) in self.names.iter().enumerate() {
+            let checked = if runs.contains(&index) {
+                " checked"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "<label><input type=\"checkbox\" name=\"run\" value=\"{index}\"{checked}> {}</label><br>\n",
+                escape_html(name)
+            ));
+        }
+        out.push_str(&format!(
+            "Depth <input type=\"number\" name=\"depth\" min=\"0\" value=\"{depth}\">\n\
+             <input type=\"submit\" value=\"Blend\">\n</form>\n\
+             <p><a href=\"/trend\">Trend</a></p>\n<table>\n<tr>"
+        ));
+        for run in runs.iter() {
+            out.push_str(&format!(
+                "<th colspan=\"2\">{}</th>",
+                escape_html(&self.names[*run])
+            ));
+        }
+        out.push_str("</tr>\n");
+        let mrl = self.blend(runs, depth)?;
+        for row in mrl.list.borrow().iter().skip(1) {
+            out.push_str("<tr>");
+            for cell in row.iter() {
+                match cell {
+                    Some(e) => out.push_str(&format!(
+                        "<td style=\"padding-left: {}em\" title=\"{}\">{:?} {}</td><td class=\"{}\">{}</td>",
+                        e.depth,
+                        escape_html(&e.failure),
+                        e.et,
+                        escape_html(&e.name),
+                        result_class(&e.result),
+                        e.result
+                    )),
+                    None => out.push_str("<td>-</td><td>-</td>"),
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n</body>\n</html>\n");
+        Ok(out)
+    }
+
+    /// Answer requests one after another until the process ends. Every
+    /// connection is read and written by its own thread, so a slow client does
+    /// not block the others, and the requests are answered here in the order
+    /// they arrive as the trees cannot be shared between threads.
+    pub fn run(&self, address: &str) -> anyhow::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        eprintln!("Serving {} runs on http://{}/", self.trees.len(), address);
+        let (requests, received) = mpsc::channel::<Request>();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let requests = requests.clone();
+                match stream {
+                    Ok(stream) => {
+                        thread::spawn(move || {
+                            if let Err(e) = respond(stream, &requests) {
+                                warn!("Request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Connection failed: {}", e),
+                }
+            }
+        });
+        for (target, reply) in received {
+            let response = self.handle(&target).unwrap_or_else(|e| Response {
+                status: "500 Internal Server Error",
+                content_type: "text/plain",
+                body: e.to_string(),
+            });
+            // The client may be gone already
+            let _ = reply.send(response);
+        }
+        Ok(())
+    }
+}
+
+/// Target of a GET request, the error response for anything else.
+/// Reading stops at the end of the headers.
+fn read_target(reader: &mut impl BufRead) -> anyhow::Result<Result<String, Response>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    if !request_line.ends_with('\n') {
+        return Ok(Err(Response::bad_request(
+            "Request incomplete or too large".to_string(),
+        )));
+    }
+    // Skip the headers
+    let mut header = String::new();
+    loop {
+        header.clear();
+        reader.read_line(&mut header)?;
+        if !header.ends_with('\n') {
+            return Ok(Err(Response::bad_request(
+                "Request incomplete or too large".to_string(),
+            )));
+        }
+        if header.len() <= 2 {
+            break;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    Ok(match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Ok(target.to_string()),
+        _ => Err(Response::bad_request("Only GET is supported".to_string())),
+    })
+}
+
+/// Read a request, wait for its response from the server and write it
+fn respond(stream: TcpStream, requests: &Sender<Request>) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_BYTES));
+    let response = match read_target(&mut reader)? {
+        Ok(target) => {
+            let (reply, response) = mpsc::channel();
+            requests
+                .send((target, reply))
+                .map_err(|_| anyhow!("Server stopped"))?;
+            response.recv()?
+        }
+        Err(response) => response,
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rf_parser::parse_tree;
+
+    fn server() -> anyhow::Result<Server> {
+        let mut trees = Vec::new();
+        for file in [
+            "robot/fixtures/rf6_output.xml",
+            "robot/fixtures/rf7_output.xml",
+        ] {
+            trees.push(parse_tree(&std::fs::read_to_string(file)?)?);
+        }
+        Ok(Server::new(
+            vec!["rf6".to_string(), "rf7 & co".to_string()],
+            trees,
+            0,
+        ))
+    }
+
+    #[test]
+    fn query_strings() {
+        assert_eq!(url_decode("Suite.Test+A%26B"), "Suite.Test A&B");
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(
+            parse_target("/api/blend?run=1&depth="),
+            (
+                "/api/blend".to_string(),
+                vec![
+                    ("run".to_string(), "1".to_string()),
+                    ("depth".to_string(), String::new())
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn endpoints() -> anyhow::Result<()> {
+        let server = server()?;
+        let index = server.handle("/?run=1")?;
+        assert_eq!(index.status, "200 OK");
+        assert!(index.body.contains("value=\"0\"> rf6"));
+        assert!(index.body.contains("value=\"1\" checked> rf7 &amp; co"));
+
+        let blend: Value = serde_json::from_str(&server.handle("/api/blend?depth=1")?.body)?;
+        assert_eq!(blend["columns"], json!(["rf6", "rf7 & co"]));
+        assert_eq!(blend["rows"][0][1]["type"], "Suite");
+        assert_eq!(blend["rows"][0][1]["result"], "Fail");
+
+        let history: Value =
+            serde_json::from_str(&server.handle("/api/history?test=Control.Loops")?.body)?;
+        assert_eq!(history["tests"].as_array().unwrap().len(), 1);
+        assert_eq!(history["tests"][0]["results"][1], "Pass");
+
+        assert_eq!(server.handle("/api/blend?run=7")?.status, "400 Bad Request");
+        assert_eq!(server.handle("/missing")?.status, "404 Not Found");
+        Ok(())
+    }
+
+    #[test]
+    fn request_limit() -> anyhow::Result<()> {
+        let read = |request: &str| {
+            let limited = request.as_bytes().take(64);
+            read_target(&mut BufReader::new(limited)).map(|r| r.map_err(|e| e.body))
+        };
+        assert_eq!(
+            read("GET /trend HTTP/1.1\r\nHost: a\r\n\r\n")?,
+            Ok("/trend".to_string())
+        );
+        let long_target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(100));
+        assert_eq!(
+            read(&long_target)?,
+            Err("Request incomplete or too large".to_string())
+        );
+        let long_header = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(100));
+        assert_eq!(
+            read(&long_header)?,
+            Err("Request incomplete or too large".to_string())
+        );
+        assert_eq!(
+            read("POST / HTTP/1.1\r\n\r\n")?,
+            Err("Only GET is supported".to_string())
+        );
+        Ok(())
+    }
+}
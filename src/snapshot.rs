@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::rc::Rc;
+use std::str;
+
+use anyhow::{anyhow, Context};
+
+use crate::element::{Element, ElementType, ResultType};
+use crate::inputs::{read_input, STDIO};
+use crate::interner::Interner;
+use crate::metadata::RunMetadata;
+use crate::rf_parser::parse_tree_with_names;
+use crate::summary::{ExecutionMessage, RunSummary, Stat};
+
+/// Start of every snapshot file
+pub const SNAPSHOT_MAGIC: &[u8; 6] = b"BRSNAP";
+
+/// Snapshot layout version, incremented on every incompatible change
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+/// Code of an element type in the binary format, new types get new codes
+fn element_type_code(et: &ElementType) -> u64 {
+    match et {
+        ElementType::File => 0,
+        ElementType::Robot => 1,
+        ElementType::Suite => 2,
+        ElementType::Test => 3,
+        ElementType::Keyword => 4,
+        ElementType::If => 5,
+        ElementType::Branch => 6,
+        ElementType::Try => 7,
+        ElementType::For => 8,
+        ElementType::Iter => 9,
+        ElementType::While => 10,
+        ElementType::Continue => 11,
+        ElementType::Group => 12,
+        ElementType::Return => 13,
+        ElementType::Break => 14,
+        ElementType::Var => 15,
+        ElementType::Error => 16,
+        ElementType::Setup => 17,
+        ElementType::Teardown => 18,
+    }
+}
+
+fn element_type(code: u64) -> anyhow::Result<ElementType> {
+    Ok(match code {
+        0 => ElementType::File,
+        1 => ElementType::Robot,
+        2 => ElementType::Suite,
+        3 => ElementType::Test,
+        4 => ElementType::Keyword,
+        5 => ElementType::If,
+        6 => ElementType::Branch,
+        7 => ElementType::Try,
+        8 => ElementType::For,
+        9 => ElementType::Iter,
+        10 => ElementType::While,
+        11 => ElementType::Continue,
+        12 => ElementType::Group,
+        13 => ElementType::Return,
+        14 => ElementType::Break,
+        15 => ElementType::Var,
+        16 => ElementType::Error,
+        17 => ElementType::Setup,
+        18 => ElementType::Teardown,
+        _ => return Err(anyhow!("Unknown element type code {code}")),
+    })
+}
+
+/// Code of a result type in the binary format
+fn result_type_code(result: &ResultType) -> u64 {
+    match result {
+        ResultType::Pass => 0,
+        ResultType::Fail => 1,
+        ResultType::NotRun => 2,
+        ResultType::None => 3,
+        ResultType::Skip => 4,
+        ResultType::PassOnRetry => 5,
+    }
+}
+
+fn result_type(code: u64) -> anyhow::Result<ResultType> {
+    Ok(match code {
+        0 => ResultType::Pass,
+        1 => ResultType::Fail,
+        2 => ResultType::NotRun,
+        3 => ResultType::None,
+        4 => ResultType::Skip,
+        5 => ResultType::PassOnRetry,
+        _ => return Err(anyhow!("Unknown result type code {code}")),
+    })
+}
+
+fn push_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Append only buffer with LEB128 varints. Strings are written as index
+/// into a table, keyword names repeat thousands of times in a run.
+#[derive(Default)]
+struct Encoder {
+    buffer: Vec<u8>,
+    strings: Vec<String>,
+    index: HashMap<String, u64>,
+}
+
+impl Encoder {
+    fn varint(&mut self, value: u64) {
+        push_varint(&mut self.buffer, value);
+    }
+
+    fn string(&mut self, s: &str) {
+        let index = match self.index.get(s) {
+            Some(index) => *index,
+            None => {
+                let index = self.strings.len() as u64;
+                self.strings.push(s.to_string());
+                self.index.insert(s.to_string(), index);
+                index
+            }
+        };
+        self.varint(index);
+    }
+
+    /// String table followed by the encoded data
+    fn finish(self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_varint(&mut data, self.strings.len() as u64);
+        for s in self.strings.iter() {
+            push_varint(&mut data, s.len() as u64);
+            data.extend_from_slice(s.as_bytes());
+        }
+        data.extend(self.buffer);
+        data
+    }
+
+    fn float(&mut self, value: Option<f64>) {
+        match value {
+            Some(value) => {
+                self.buffer.push(1);
+                self.buffer.extend_from_slice(&value.to_le_bytes());
+            }
+            None => self.buffer.push(0),
+        }
+    }
+
+    fn stats(&mut self, stats: &[Stat]) {
+        self.varint(stats.len() as u64);
+        for stat in stats {
+            self.string(&stat.name);
+            self.varint(stat.pass as u64);
+            self.varint(stat.fail as u64);
+            self.varint(stat.skip as u64);
+        }
+    }
+
+    fn summary(&mut self, summary: &RunSummary) {
+        let metadata = &summary.metadata;
+        self.string(&metadata.generator);
+        self.string(&metadata.generated);
+        self.varint(metadata.rpa as u64);
+        self.string(&metadata.schemaversion);
+        self.varint(metadata.suite_metadata.len() as u64);
+        for (name, value) in metadata.suite_metadata.iter() {
+            self.string(name);
+            self.string(value);
+        }
+        self.stats(&summary.total);
+        self.stats(&summary.tags);
+        self.stats(&summary.suites);
+        self.varint(summary.errors.len() as u64);
+        for error in summary.errors.iter() {
+            self.string(&error.timestamp);
+            self.string(&error.level);
+            self.string(&error.text);
+        }
+    }
+
+    fn element(&mut self, element: &Element) {
+        self.varint(element_type_code(&element.et));
+        self.varint(result_type_code(&element.result));
+        self.string(&element.name);
+        self.string(&element.message);
+        self.string(&element.owner);
+        self.float(element.elapsed);
+        let children = element.children.borrow();
+        self.varint(children.len() as u64);
+        for child in children.iter() {
+            self.element(child);
+        }
+    }
+}
+
+/// Reading position in an encoded buffer
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    strings: Vec<Rc<str>>,
+    names: &'a mut Interner,
+}
+
+impl<'a> Decoder<'a> {
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| anyhow!("Truncated data at {}", self.pos))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| anyhow!("Truncated data at {}", self.pos))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> anyhow::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("Varint too long at {}", self.pos))
+    }
+
+    fn count(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::try_from(self.varint()?)?)
+    }
+
+    fn string_table(&mut self) -> anyhow::Result<()> {
+        for _ in 0..self.varint()? {
+            let len = self.varint()? as usize;
+            let s = str::from_utf8(self.bytes(len)?)?;
+            let s = self.names.intern(s);
+            self.strings.push(s);
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> anyhow::Result<Rc<str>> {
+        let index = self.varint()? as usize;
+        self.strings
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown string {index} at {}", self.pos))
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        Ok(self.name()?.to_string())
+    }
+
+    fn float(&mut self) -> anyhow::Result<Option<f64>> {
+        match self.byte()? {
+            0 => Ok(None),
+            _ => Ok(Some(f64::from_le_bytes(self.bytes(8)?.try_into()?))),
+        }
+    }
+
+    fn stats(&mut self) -> anyhow::Result<Vec<Stat>> {
+        let mut stats = Vec::new();
+        for _ in 0..self.varint()? {
+            stats.push(Stat {
+                name: self.string()?,
+                pass: self.count()?,
+                fail: self.count()?,
+                skip: self.count()?,
+            });
+        }
+        Ok(stats)
+    }
+
+    fn summary(&mut self) -> anyhow::Result<RunSummary> {
+        let mut metadata = RunMetadata {
+            generator: self.string()?,
+            generated: self.string()?,
+            rpa: self.varint()? != 0,
+            schemaversion: self.string()?,
+            suite_metadata: Vec::new(),
+        };
+        for _ in 0..self.varint()? {
+            metadata
+                .suite_metadata
+                .push((self.string()?, self.string()?));
+        }
+        let total = self.stats()?;
+        let tags = self.stats()?;
+        let suites = self.stats()?;
+        let mut errors = Vec::new();
+        for _ in 0..self.varint()? {
+            errors.push(ExecutionMessage {
+                timestamp: self.string()?,
+                level: self.string()?,
+                text: self.string()?,
+            });
+        }
+        Ok(RunSummary {
+            metadata,
+            total,
+            tags,
+            suites,
+            errors,
+        })
+    }
+
+    fn element(&mut self) -> anyhow::Result<Element> {
+        let et = element_type(self.varint()?)?;
+        let result = result_type(self.varint()?)?;
+        let mut element = Element::new(et, self.name()?);
+        element.result = result;
+        element.message = self.string()?;
+        element.owner = self.name()?;
+        element.elapsed = self.float()?;
+        for _ in 0..self.varint()? {
+            let child = self.element()?;
+            element.children.borrow_mut().push(Rc::new(child));
+        }
+        Ok(element)
+    }
+}
+
+/// Encode a parsed tree together with the run summary of its root
+pub fn encode_tree(tree: &Element) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    match &tree.summary {
+        Some(summary) => {
+            encoder.buffer.push(1);
+            encoder.summary(summary);
+        }
+        None => encoder.buffer.push(0),
+    }
+    encoder.element(tree);
+    encoder.finish()
+}
+
+/// Decode a tree written by `encode_tree`, names are shared through the interner
+pub fn decode_tree(data: &[u8], names: &mut Interner) -> anyhow::Result<Element> {
+    let mut decoder = Decoder {
+        data,
+        pos: 0,
+        strings: Vec::new(),
+        names,
+    };
+    decoder.string_table()?;
+    let summary = match decoder.byte()? {
+        0 => None,
+        _ => Some(Rc::new(decoder.summary()?)),
+    };
+    let mut tree = decoder.element()?;
+    tree.summary = summary;
+    if decoder.pos != data.len() {
+        return Err(anyhow!("Trailing data at {}", decoder.pos));
+    }
+    Ok(tree)
+}
+
+/// Versioned snapshot of a parsed output, only the tree and summary without log messages
+pub fn encode_snapshot(tree: &Element) -> Vec<u8> {
+    let mut data = SNAPSHOT_MAGIC.to_vec();
+    push_varint(&mut data, SNAPSHOT_VERSION);
+    data.extend(encode_tree(tree));
+    data
+}
+
+/// Decode a snapshot written by `encode_snapshot`
+pub fn decode_snapshot(data: &[u8], names: &mut Interner) -> anyhow::Result<Element> {
+    let rest = data
+        .strip_prefix(SNAPSHOT_MAGIC.as_slice())
+        .ok_or_else(|| anyhow!("Not a snapshot"))?;
+    let mut decoder = Decoder {
+        data: rest,
+        pos: 0,
+        strings: Vec::new(),
+        names,
+    };
+    let version = decoder.varint()?;
+    if version != SNAPSHOT_VERSION {
+        return Err(anyhow!(
+            "Snapshot version {version} is not supported, expected {SNAPSHOT_VERSION}"
+        ));
+    }
+    let pos = decoder.pos;
+    decode_tree(&rest[pos..], decoder.names)
+}
+
+/// Write the snapshot of a parsed output
+pub fn save_snapshot(tree: &Element, path: &str) -> anyhow::Result<()> {
+    fs::write(path, encode_snapshot(tree)).context(format!("Cannot write {}", path))
+}
+
+/// Read a snapshot written by `save_snapshot`
+pub fn load_snapshot(path: &str) -> anyhow::Result<Element> {
+    let data = fs::read(path).context(format!("File not found {}", path))?;
+    decode_snapshot(&data, &mut Interner::default())
+        .context(format!("Cannot load snapshot {}", path))
+}
+
+/// True when the file starts like a snapshot
+pub fn is_snapshot(path: &str) -> bool {
+    let mut magic = [0u8; 6];
+    path != STDIO
+        && fs::File::open(path)
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .is_ok()
+        && &magic == SNAPSHOT_MAGIC
+}
+
+/// Parse an output file or load a snapshot, names are shared through the interner
+pub fn read_tree(path: &str, names: &mut Interner) -> anyhow::Result<Element> {
+    if is_snapshot(path) {
+        let data = fs::read(path).context(format!("File not found {}", path))?;
+        decode_snapshot(&data, names).context(format!("Cannot load snapshot {}", path))
+    } else {
+        parse_tree_with_names(&read_input(path)?, names)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blend_results::blend_trees;
+    use crate::rf_parser::parse_tree;
+
+    fn decode(data: &[u8]) -> anyhow::Result<Element> {
+        decode_tree(data, &mut Interner::default())
+    }
+
+    #[test]
+    fn encode_round_trip() -> anyhow::Result<()> {
+        let xml = std::fs::read_to_string("robot/fixtures/rf7_output.xml")?;
+        let tree = parse_tree(&xml)?;
+        let data = encode_tree(&tree);
+        let decoded = decode(&data)?;
+        assert_eq!(decoded, tree);
+        assert_eq!(decoded.summary, tree.summary);
+
+        let names = vec!["a".to_string()];
+        let csv = |t: &Element| blend_trees(std::slice::from_ref(t), &names, 0, false);
+        assert_eq!(
+            csv(&decoded)?.dump_to_csv_str()?,
+            csv(&tree)?.dump_to_csv_str()?
+        );
+        assert!(decode(&data[..data.len() - 1]).is_err());
+        // One string of u64::MAX bytes
+        let huge = [
+            1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+        ];
+        assert!(decode(&huge).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn type_codes() {
+        for code in 0..19 {
+            assert_eq!(element_type_code(&element_type(code).unwrap()), code);
+        }
+        for code in 0..6 {
+            assert_eq!(result_type_code(&result_type(code).unwrap()), code);
+        }
+        assert!(element_type(19).is_err());
+        assert!(result_type(6).is_err());
+    }
+
+    #[test]
+    fn snapshot_file() -> anyhow::Result<()> {
+        let xml_file = "robot/fixtures/rf7_output.xml";
+        let tree = parse_tree(&std::fs::read_to_string(xml_file)?)?;
+        let path = std::env::temp_dir().join("blend_result_snapshot_test.brs");
+        let path = path.to_string_lossy().to_string();
+        save_snapshot(&tree, &path)?;
+        assert!(is_snapshot(&path));
+        assert!(!is_snapshot(xml_file));
+        let mut names = Interner::default();
+        let loaded = read_tree(&path, &mut names)?;
+        assert_eq!(loaded, tree);
+        let parsed = read_tree(xml_file, &mut names)?;
+        assert_eq!(parsed, tree);
+        // Both trees share the names
+        let first = |t: &Element| t.children.borrow()[0].name.clone();
+        assert!(Rc::ptr_eq(&first(&loaded), &first(&parsed)));
+
+        let mut data = std::fs::read(&path)?;
+        data[SNAPSHOT_MAGIC.len()] = 99;
+        let error = decode_snapshot(&data, &mut Interner::default()).unwrap_err();
+        assert!(error.to_string().contains("version 99"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
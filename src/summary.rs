@@ -0,0 +1,224 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+use crate::metadata::RunMetadata;
+use crate::rf_parser::get_attribute_kv;
+use crate::timestamp::normalize_timestamp;
+
+/// One line of the statistics section
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Stat {
+    pub name: String,
+    pub pass: u32,
+    pub fail: u32,
+    pub skip: u32,
+}
+
+/// Execution error or warning like a deprecated keyword or a failed import
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExecutionMessage {
+    pub timestamp: String,
+    pub level: String,
+    pub text: String,
+}
+
+/// Run metadata together with the statistics and execution errors
+/// at the end of an output file
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunSummary {
+    pub metadata: RunMetadata,
+    pub total: Vec<Stat>,
+    pub tags: Vec<Stat>,
+    pub suites: Vec<Stat>,
+    pub errors: Vec<ExecutionMessage>,
+}
+
+impl RunSummary {
+    /// Rows of the summary section: section, name, pass, fail, skip, time and message
+    pub fn rows(&self) -> Vec<[String; 7]> {
+        let mut rows = Vec::new();
+        for (name, value) in self.metadata.rows() {
+            rows.push([
+                "Metadata".to_string(),
+                name,
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                value,
+            ]);
+        }
+        for (section, stats) in [
+            ("Total", &self.total),
+            ("Tag", &self.tags),
+            ("Suite", &self.suites),
+        ] {
+            for stat in stats.iter() {
+                rows.push([
+                    section.to_string(),
+                    stat.name.clone(),
+                    stat.pass.to_string(),
+                    stat.fail.to_string(),
+                    stat.skip.to_string(),
+                    String::new(),
+                    String::new(),
+                ]);
+            }
+        }
+        for error in self.errors.iter() {
+            rows.push([
+                "Error".to_string(),
+                error.level.clone(),
+                String::new(),
+                String::new(),
+                String::new(),
+                error.timestamp.clone(),
+                error.text.clone(),
+            ]);
+        }
+        rows
+    }
+}
+
+/// Section of the output file the reader is currently in
+#[derive(Debug, PartialEq)]
+enum Section {
+    None,
+    Total,
+    Tag,
+    Suite,
+    Errors,
+}
+
+fn stat_from(decoder: quick_xml::encoding::Decoder, e: &BytesStart) -> Stat {
+    let mut stat = Stat::default();
+    for a in e.attributes() {
+        let (key, value) = get_attribute_kv(decoder, a);
+        match key.as_str() {
+            "pass" => stat.pass = value.parse().unwrap_or(0),
+            "fail" => stat.fail = value.parse().unwrap_or(0),
+            "skip" => stat.skip = value.parse().unwrap_or(0),
+            _ => (),
+        }
+    }
+    stat
+}
+
+fn message_from(decoder: quick_xml::encoding::Decoder, e: &BytesStart) -> ExecutionMessage {
+    let mut message = ExecutionMessage::default();
+    for a in e.attributes() {
+        let (key, value) = get_attribute_kv(decoder, a);
+        match key.as_str() {
+            "level" => message.level = value,
+            "time" | "timestamp" => message.timestamp = normalize_timestamp(&value),
+            _ => (),
+        }
+    }
+    message
+}
+
+/// Read the statistics and errors sections.
+/// Continues where the element parser stopped at the statistics element.
+pub fn parse_summary(reader: &mut Reader<&[u8]>) -> anyhow::Result<RunSummary> {
+    let decoder = reader.decoder();
+    let mut summary = RunSummary::default();
+    let mut section = Section::None;
+    let mut stat: Option<Stat> = None;
+    let mut message: Option<ExecutionMessage> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"total" => section = Section::Total,
+                b"tag" => section = Section::Tag,
+                b"suite" => section = Section::Suite,
+                b"errors" => section = Section::Errors,
+                b"stat" => stat = Some(stat_from(decoder, &e)),
+                b"msg" if section == Section::Errors => message = Some(message_from(decoder, &e)),
+                _ => (),
+            },
+            Event::Text(e) => {
+                let text = e.unescape()?;
+                if let Some(stat) = stat.as_mut() {
+                    stat.name = text.to_string();
+                } else if let Some(message) = message.as_mut() {
+                    message.text = text.to_string();
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"stat" => {
+                    if let Some(stat) = stat.take() {
+                        match section {
+                            Section::Total => summary.total.push(stat),
+                            Section::Tag => summary.tags.push(stat),
+                            Section::Suite => summary.suites.push(stat),
+                            _ => (),
+                        }
+                    }
+                }
+                b"msg" => {
+                    if let Some(message) = message.take() {
+                        summary.errors.push(message);
+                    }
+                }
+                b"total" | b"tag" | b"suite" | b"errors" => section = Section::None,
+                b"robot" => break,
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn statistics_and_errors() -> anyhow::Result<()> {
+        let xml = r#"<robot>
+<statistics>
+<total>
+<stat pass="2" fail="1" skip="0">All Tests</stat>
+</total>
+<tag>
+<stat pass="1" fail="0" skip="1" info="combined">smoke</stat>
+</tag>
+<suite>
+<stat pass="2" fail="1" skip="0" id="s1" name="Demo">Demo</stat>
+</suite>
+</statistics>
+<errors>
+<msg time="2024-01-31T12:00:00.123456" level="WARN">Keyword 'Old' is deprecated.</msg>
+<msg timestamp="20240131 12:00:01.000" level="ERROR">Importing library 'Missing' failed</msg>
+</errors>
+</robot>"#;
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let summary = parse_summary(&mut reader)?;
+        assert_eq!(
+            summary.total,
+            vec![Stat {
+                name: "All Tests".to_string(),
+                pass: 2,
+                fail: 1,
+                skip: 0
+            }]
+        );
+        assert_eq!(summary.tags[0].name, "smoke");
+        assert_eq!(summary.tags[0].skip, 1);
+        assert_eq!(summary.suites[0].name, "Demo");
+        assert_eq!(
+            summary.errors[1],
+            ExecutionMessage {
+                timestamp: "2024-01-31T12:00:01.000".to_string(),
+                level: "ERROR".to_string(),
+                text: "Importing library 'Missing' failed".to_string(),
+            }
+        );
+        // Metadata is read separately, only rpa of the default is listed
+        assert_eq!(summary.rows().len(), 1 + 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+/// Bring the timestamps of different robotframework versions into one sortable format.
+/// RF 7 writes `2024-01-31T12:00:00.123456`, older versions `20240131 12:00:00.123`.
+/// The older format becomes `2024-01-31T12:00:00.123`, RF 7 timestamps are kept
+/// with their microseconds. Both sort correctly as strings.
+pub fn normalize_timestamp(timestamp: &str) -> String {
+    let ts = timestamp.trim();
+    let b = ts.as_bytes();
+    if b.len() >= 17 && b[8] == b' ' && b[..8].iter().all(u8::is_ascii_digit) {
+        format!("{}-{}-{}T{}", &ts[0..4], &ts[4..6], &ts[6..8], &ts[9..])
+    } else {
+        ts.to_string()
+    }
+}
+
+/// Seconds since 1970 of a timestamp in either robotframework format.
+/// Returns None for unparseable values like `N/A` of not executed elements.
+pub fn timestamp_seconds(timestamp: &str) -> Option<f64> {
+    let ts = normalize_timestamp(timestamp);
+    let (date, time) = ts.split_once('T')?;
+    let mut date = date.split('-').map(|p| p.parse::<i64>());
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.split(':');
+    let hours = time.next()?.parse::<f64>().ok()?;
+    let minutes = time.next()?.parse::<f64>().ok()?;
+    let seconds = time.next()?.parse::<f64>().ok()?;
+    let days = days_from_civil(year, month, day) as f64;
+    Some(days * 86400.0 + hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Seconds between two timestamps
+pub fn elapsed_seconds(start: &str, end: &str) -> Option<f64> {
+    Some(timestamp_seconds(end)? - timestamp_seconds(start)?)
+}
+
+/// Days since 1970-01-01 of a gregorian date
+/// See http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_timestamp("20240131 12:00:00.123"),
+            "2024-01-31T12:00:00.123"
+        );
+        assert_eq!(
+            normalize_timestamp("2024-01-31T12:00:00.123456"),
+            "2024-01-31T12:00:00.123456"
+        );
+        assert_eq!(normalize_timestamp(""), "");
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(timestamp_seconds("19700102 00:00:01.500"), Some(86401.5));
+        assert_eq!(timestamp_seconds("N/A"), None);
+        let elapsed = elapsed_seconds("20231231 23:59:59.000", "2024-01-01T00:00:01.250000");
+        assert_eq!(elapsed, Some(2.25));
+    }
+}
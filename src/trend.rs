@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fs;
+
+use anyhow::Context;
+use colored::Colorize;
+use csv::Writer;
+
+use crate::element::{Element, ElementType, ResultType};
+use crate::rf_parser::{generated_timestamp, parse_tree};
+
+/// One input run of a trend report
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunInfo {
+    pub label: String,
+    /// Normalised generation time of the run
+    pub date: String,
+}
+
+/// Results of a single test over all runs, identified by its suite path
+#[derive(Debug, PartialEq)]
+pub struct TestTrend {
+    pub path: String,
+    /// One entry per run, None when the test was not part of the run
+    pub results: Vec<Option<ResultType>>,
+    pub durations: Vec<f64>,
+}
+
+impl TestTrend {
+    /// Compact status string with one character per run e.g. `PPF-S`
+    pub fn status_string(&self) -> String {
+        self.results.iter().map(result_char).collect()
+    }
+
+    /// Ratio of failed runs to the runs that contain the test
+    pub fn failure_rate(&self) -> f64 {
+        let present = self.results.iter().flatten().count();
+        if present == 0 {
+            return 0.0;
+        }
+        let failed = self
+            .results
+            .iter()
+            .flatten()
+            .filter(|r| **r == ResultType::Fail)
+            .count();
+        failed as f64 / present as f64
+    }
+
+    /// Date of the latest run where the test failed
+    pub fn last_failure<'a>(&self, runs: &'a [RunInfo]) -> Option<&'a str> {
+        self.results
+            .iter()
+            .zip(runs.iter())
+            .filter(|(r, _)| **r == Some(ResultType::Fail))
+            .map(|(_, run)| run.date.as_str())
+            .next_back()
+    }
+
+    pub fn median_duration(&self) -> Option<f64> {
+        let mut durations = self.durations.clone();
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_by(|a, b| a.total_cmp(b));
+        let mid = durations.len() / 2;
+        if durations.len().is_multiple_of(2) {
+            Some((durations[mid - 1] + durations[mid]) / 2.0)
+        } else {
+            Some(durations[mid])
+        }
+    }
+}
+
+fn result_char(result: &Option<ResultType>) -> char {
+    match result {
+        Some(ResultType::Pass) => 'P',
+        Some(ResultType::Fail) => 'F',
+        Some(ResultType::Skip) => 'S',
+        Some(ResultType::NotRun) => 'N',
+        Some(ResultType::PassOnRetry) => 'R',
+        Some(ResultType::None) => '?',
+        None => '-',
+    }
+}
+
+/// Per test trend over many runs
+#[derive(Debug)]
+pub struct TrendReport {
+    pub runs: Vec<RunInfo>,
+    pub tests: Vec<TestTrend>,
+}
+
+impl TrendReport {
+    /// Collect the tests of all trees, tests are ordered by their first appearance
+    pub fn new(runs: Vec<RunInfo>, trees: &[Element]) -> Self {
+        let mut tests: Vec<TestTrend> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for (index, tree) in trees.iter().enumerate() {
+            let mut found = Vec::new();
+            collect_tests(tree, "", &mut found);
+            for (path, result, elapsed) in found {
+                let pos = *positions.entry(path.clone()).or_insert_with(|| {
+                    tests.push(TestTrend {
+                        path,
+                        results: vec![None; trees.len()],
+                        durations: Vec::new(),
+                    });
+                    tests.len() - 1
+                });
+                let test = &mut tests[pos];
+                test.results[index] = Some(result);
+                if let Some(elapsed) = elapsed {
+                    test.durations.push(elapsed);
+                }
+            }
+        }
+        TrendReport { runs, tests }
+    }
+
+    pub fn to_terminal(&self) -> String {
+        let width = self.tests.iter().map(|t| t.path.len()).max().unwrap_or(4);
+        let mut out = String::new();
+        for (index, run) in self.runs.iter().enumerate() {
+            out.push_str(&format!("{:>3} {} {}\n", index, run.date, run.label));
+        }
+        out.push_str(&format!(
+            "{:<width$} {:<runs$} {:>7} {:>10} {}\n",
+            "Test",
+            "Status",
+            "Fail %",
+            "Median s",
+            "Last failure",
+            runs = self.runs.len().max(6)
+        ));
+        for test in self.tests.iter() {
+            let status: String = test
+                .status_string()
+                .chars()
+                .map(|c| match c {
+                    'P' => c.to_string().green().to_string(),
+                    'F' => c.to_string().red().to_string(),
+                    'S' => c.to_string().blue().to_string(),
+                    'R' => c.to_string().yellow().to_string(),
+                    _ => c.to_string(),
+                })
+                .collect();
+            let pad = " ".repeat(self.runs.len().max(6) - self.runs.len());
+            out.push_str(&format!(
+                "{:<width$} {}{} {:>7.1} {:>10} {}\n",
+                test.path,
+                status,
+                pad,
+                test.failure_rate() * 100.0,
+                format_duration(test.median_duration()),
+                test.last_failure(&self.runs).unwrap_or("-")
+            ));
+        }
+        out
+    }
+
+    pub fn to_csv(&self) -> anyhow::Result<String> {
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.write_record([
+            "Test",
+            "Status",
+            "Failure rate",
+            "Last failure",
+            "Median duration",
+        ])?;
+        for test in self.tests.iter() {
+            wtr.write_record(&[
+                test.path.to_string(),
+                test.status_string(),
+                format!("{:.3}", test.failure_rate()),
+                test.last_failure(&self.runs).unwrap_or("").to_string(),
+                test.median_duration()
+                    .map(|d| format!("{d:.3}"))
+                    .unwrap_or_default(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(String::from_utf8(wtr.into_inner()?)?)
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Trend</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; font-size: 10pt; }\n\
+             td, th { padding: 2px 8px; text-align: left; }\n\
+             .status { font-family: monospace; }\n\
+             .P { background: #90ee90; } .F { background: #ffbcb8; }\n\
+             .S { background: #add8e6; } .N { background: #d3d3d3; }\n\
+             .R { background: #ffffa0; }\n\
+             </style>\n</head>\n<body>\n<h1>Trend</h1>\n<ol start=\"0\">\n",
+        );
+        for run in self.runs.iter() {
+            out.push_str(&format!(
+                "<li>{} {}</li>\n",
+                escape_html(&run.date),
+                escape_html(&run.label)
+            ));
+        }
+        out.push_str(
+            "</ol>\n<table>\n<tr><th>Test</th><th>Status</th><th>Failure rate</th>\
+             <th>Median duration</th><th>Last failure</th></tr>\n",
+        );
+        for test in self.tests.iter() {
+            let status: String = test
+                .status_string()
+                .chars()
+                .zip(self.runs.iter())
+                .map(|(c, run)| {
+                    format!(
+                        "<span class=\"{c}\" title=\"{}\">{c}</span>",
+                        escape_html(&run.label)
+                    )
+                })
+                .collect();
+            out.push_str(&format!(
+                "<tr><td>{}</td><td class=\"status\">{}</td><td>{:.1} %</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&test.path),
+                status,
+                test.failure_rate() * 100.0,
+                format_duration(test.median_duration()),
+                escape_html(test.last_failure(&self.runs).unwrap_or("-"))
+            ));
+        }
+        out.push_str("</table>\n</body>\n</html>\n");
+        out
+    }
+}
+
+/// Find all tests with their suite path joined by dots
+fn collect_tests(
+    element: &Element,
+    prefix: &str,
+    found: &mut Vec<(String, ResultType, Option<f64>)>,
+) {
+    for child in element.children.borrow().iter() {
+        let path = if prefix.is_empty() {
+            child.name.to_string()
+        } else {
+            format!("{}.{}", prefix, child.name)
+        };
+        match child.et {
+            ElementType::Suite => collect_tests(child, &path, found),
+            ElementType::Test => found.push((path, child.result.clone(), child.elapsed)),
+            _ => (),
+        }
+    }
+}
+
+fn format_duration(duration: Option<f64>) -> String {
+    match duration {
+        Some(d) => format!("{d:.3}"),
+        None => "-".to_string(),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Run infos of already parsed trees with the date from their run metadata
+pub fn run_infos(names: &[String], trees: &[Element]) -> Vec<RunInfo> {
+    names
+        .iter()
+        .zip(trees.iter())
+        .map(|(name, tree)| RunInfo {
+            label: name.clone(),
+            date: tree
+                .summary
+                .as_ref()
+                .map(|s| s.metadata.generated.clone())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Read and parse XML files together with their generation date
+pub fn load_runs(xml_files: &[String]) -> anyhow::Result<(Vec<RunInfo>, Vec<Element>)> {
+    let mut runs = Vec::new();
+    let mut trees = Vec::new();
+    for xml_file in xml_files {
+        eprintln!("Parsing {}", xml_file);
+        let xml = fs::read_to_string(xml_file).context(format!("File not found {}", xml_file))?;
+        runs.push(RunInfo {
+            label: xml_file.to_string(),
+            date: generated_timestamp(&xml)?,
+        });
+        trees.push(parse_tree(&xml)?);
+    }
+    Ok((runs, trees))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    fn run(results: &[(&str, ResultType, f64)]) -> Element {
+        let root = Element::new(ElementType::Robot, String::new());
+        let suite = Element::new(ElementType::Suite, "Suite".to_string());
+        for (name, result, elapsed) in results {
+            let mut test = Element::new(ElementType::Test, name.to_string());
+            test.result = result.clone();
+            test.elapsed = Some(*elapsed);
+            suite.children.borrow_mut().push(Rc::new(test));
+        }
+        root.children.borrow_mut().push(Rc::new(suite));
+        root
+    }
+
+    fn report() -> TrendReport {
+        let trees = vec![
+            run(&[("A", ResultType::Pass, 1.0), ("B", ResultType::Pass, 2.0)]),
+            run(&[("A", ResultType::Fail, 3.0), ("C", ResultType::Skip, 1.0)]),
+            run(&[("A", ResultType::Pass, 2.0), ("B", ResultType::Fail, 4.0)]),
+            run(&[("A", ResultType::Fail, 10.0)]),
+        ];
+        let runs = (0..trees.len())
+            .map(|i| RunInfo {
+                label: format!("run{i}"),
+                date: format!("2024-01-0{}", i + 1),
+            })
+            .collect();
+        TrendReport::new(runs, &trees)
+    }
+
+    #[test]
+    fn trend_per_test() {
+        let report = report();
+        let paths: Vec<&str> = report.tests.iter().map(|t| t.path.as_str()).collect();
+        assert_eq!(paths, vec!["Suite.A", "Suite.B", "Suite.C"]);
+
+        let a = &report.tests[0];
+        assert_eq!(a.status_string(), "PFPF");
+        assert_eq!(a.failure_rate(), 0.5);
+        assert_eq!(a.last_failure(&report.runs), Some("2024-01-04"));
+        assert_eq!(a.median_duration(), Some(2.5));
+
+        let b = &report.tests[1];
+        assert_eq!(b.status_string(), "P-F-");
+        assert_eq!(b.last_failure(&report.runs), Some("2024-01-03"));
+        assert_eq!(report.tests[2].status_string(), "-S--");
+        assert_eq!(report.tests[2].last_failure(&report.runs), None);
+    }
+
+    #[test]
+    fn trend_outputs() -> anyhow::Result<()> {
+        let report = report();
+        let csv = report.to_csv()?;
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("Suite.A,PFPF,0.500,2024-01-04,2.500")
+        );
+        let html = report.to_html();
+        assert!(html.contains("<td>Suite.C</td>"));
+        assert!(html.contains("<span class=\"S\" title=\"run1\">S</span>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use log::warn;
+
+use crate::element::Element;
+use crate::inputs::find_outputs;
+use crate::interner::Interner;
+use crate::metadata::column_labels;
+use crate::snapshot::{is_snapshot, read_tree};
+use crate::trend::{run_infos, TrendReport};
+
+/// True when the output file has been written completely.
+/// Robot Framework writes the closing robot tag last.
+pub fn is_complete(path: &Path) -> bool {
+    let Ok(mut file) = fs::File::open(path) else {
+        return false;
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(64))).is_ok()
+        && file.read_to_end(&mut tail).is_ok()
+        && String::from_utf8_lossy(&tail)
+            .trim_end()
+            .ends_with("</robot>")
+}
+
+/// Results of a directory that grows while a test campaign runs.
+/// Every output is parsed once and kept for blending. The kept trees are
+/// blended again for every new output instead of appending a column, as a
+/// new output can add rows between the ones of the earlier outputs.
+pub struct Watcher {
+    pub dir: PathBuf,
+    /// Column headers like the ones of blend
+    pub names: Vec<String>,
+    pub trees: Vec<Element>,
+    labels: Vec<(String, String)>,
+    /// Outputs that are blended or could not be parsed
+    seen: Vec<PathBuf>,
+    /// Names shared by all trees of the campaign
+    interner: Interner,
+}
+
+impl Watcher {
+    pub fn new(dir: &str) -> Self {
+        Watcher {
+            dir: PathBuf::from(dir),
+            names: Vec::new(),
+            trees: Vec::new(),
+            labels: Vec::new(),
+            seen: Vec::new(),
+            interner: Interner::default(),
+        }
+    }
+
+    /// Column header for outputs, e.g. nightly=results/job1/output.xml
+    pub fn with_labels(mut self, labels: &[(String, String)]) -> Self {
+        self.labels = labels.to_vec();
+        self
+    }
+
+    /// Parse the outputs that were finished since the last poll, returns their paths
+    pub fn poll(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut added = Vec::new();
+        for path in find_outputs(&self.dir)? {
+            let name = path.to_string_lossy().to_string();
+            if self.seen.contains(&path) || !(is_snapshot(&name) || is_complete(&path)) {
+                continue;
+            }
+            self.seen.push(path.clone());
+            // A broken file must not end a long campaign
+            match read_tree(&name, &mut self.interner) {
+                Ok(tree) => {
+                    let label = column_labels(
+                        std::slice::from_ref(&name),
+                        std::slice::from_ref(&tree),
+                        &self.labels,
+                    );
+                    self.names.extend(label);
+                    self.trees.push(tree);
+                    added.push(name);
+                }
+                Err(e) => warn!("Skipping {}: {}", name, e),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Write a HTML trend report of all outputs when a file is given
+    pub fn save_trend(&self, html_file: Option<&str>) -> anyhow::Result<()> {
+        if let Some(html_file) = html_file {
+            let report = TrendReport::new(run_infos(&self.names, &self.trees), &self.trees);
+            fs::write(html_file, report.to_html())
+                .context(format!("Cannot write {}", html_file))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blend_results::save_to_csv;
+    use crate::blender::Blender;
+
+    #[test]
+    fn only_finished_outputs() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("blend_result_watch_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("job1"))?;
+        fs::create_dir_all(dir.join("job2"))?;
+        let xml = fs::read_to_string("robot/fixtures/rf7_output.xml")?;
+        fs::write(dir.join("job1/output.xml"), &xml)?;
+        // Still being written
+        let partial = &xml[..xml.find("<statistics>").unwrap()];
+        fs::write(dir.join("job2/output.xml"), partial)?;
+        fs::write(dir.join("job2/log.html"), "")?;
+
+        let output = dir.join("job1/output.xml").to_string_lossy().to_string();
+        let labels = [("first".to_string(), output)];
+        let mut watcher = Watcher::new(&dir.to_string_lossy()).with_labels(&labels);
+        let added = watcher.poll()?;
+        assert_eq!(added.len(), 1);
+        assert!(added[0].ends_with("output.xml"));
+        assert!(watcher.poll()?.is_empty());
+
+        fs::write(dir.join("job2/output.xml"), &xml)?;
+        assert_eq!(watcher.poll()?.len(), 1);
+        assert_eq!(watcher.trees.len(), 2);
+
+        let csv_file = dir.join("watch.csv").to_string_lossy().to_string();
+        let html_file = dir.join("watch.html").to_string_lossy().to_string();
+        let trees: Vec<&Element> = watcher.trees.iter().collect();
+        save_to_csv(
+            &Blender::new().run_trees(&watcher.names, &trees)?,
+            &csv_file,
+        )?;
+        watcher.save_trend(Some(&html_file))?;
+        let csv = fs::read_to_string(&csv_file)?;
+        assert!(csv.starts_with("Type 0,Name 0,Result 0,Owner 0,Type 1,Name 1,Result 1,Owner 1\n"));
+        assert!(watcher.names[0].starts_with("first ("));
+        assert!(fs::read_to_string(&html_file)?.contains("<html"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use blend_result::blend_results::{blend_trees, parse_files};
+use blend_result::element::Element;
+use blend_result::export::CsvExporter;
+use blend_result::filter::Level;
+use blend_result::{Blender, Exporter, Filter, ResultFile};
+mod common;
+
+#[test]
+fn test_blend_diff_only() -> anyhow::Result<()> {
+    common::init_logger();
+    let files = vec![
+        "robot/fixtures/rf5_output.xml".to_string(),
+        "robot/fixtures/rf6_output.xml".to_string(),
+    ];
+    let trees = parse_files(&files)?;
+    let names = vec!["rf5".to_string(), "rf6".to_string()];
+
+    let full = blend_trees(&trees, &names, 0, false)?;
+    assert_eq!(full.list.borrow().len(), 35);
+
+    // Passing subtrees that are the same in both are collapsed, the loops
+    // test passed in both but differs and is expanded like the failing test
+    let diff = blend_trees(&trees, &names, 0, true)?;
+    let expect = "Type 0,Name 0,Result 0,Owner 0,Type 1,Name 1,Result 1,Owner 1\n\
+        File,rf5,None,,File,rf6,None,\n\
+        Suite,Control,Fail,,Suite,Control,Fail,\n\
+        Setup,Log,Pass,BuiltIn,Setup,Log,Pass,BuiltIn\n\
+        Test,Loops,Pass,,Test,Loops,Pass,\n\
+        Setup,No Operation,Pass,BuiltIn,Setup,No Operation,Pass,BuiltIn\n\
+        For,,Pass,,For,,Pass,\n\
+        Iter,,Pass,,Iter,,Pass,\n\
+        Keyword,Log,Pass,BuiltIn,If,,Pass,\n\
+        -,-,-,-,Branch,,NotRun,\n\
+        -,-,-,-,Continue,,NotRun,\n\
+        -,-,-,-,Keyword,Log,Pass,BuiltIn\n\
+        Iter,,Pass,,Iter,,Pass,\n\
+        Continue,,Pass,,If,,Pass,\n\
+        -,-,-,-,Branch,,Pass,\n\
+        -,-,-,-,Continue,,Pass,\n\
+        -,-,-,-,Keyword,Log,NotRun,BuiltIn\n\
+        While,,Pass,,While,,Pass,\n\
+        Iter,,Pass,,Iter,,Pass,\n\
+        Break,,Pass,,Keyword,No Operation,Pass,BuiltIn\n\
+        -,-,-,-,Iter,,Pass,\n\
+        -,-,-,-,Keyword,No Operation,Pass,BuiltIn\n\
+        -,-,-,-,Iter,,Pass,\n\
+        -,-,-,-,Keyword,No Operation,Pass,BuiltIn\n\
+        Keyword,Get Answer,Pass,,Keyword,Answer Is 42,Pass,\n\
+        Return,,Pass,,Return,,Pass,\n\
+        Teardown,Log,Pass,BuiltIn,Teardown,Log,Pass,BuiltIn\n\
+        Test,Errors,Fail,,Test,Errors,Fail,\n\
+        Try,,Fail,,Try,,Fail,\n\
+        Branch,,Fail,,Branch,,Fail,\n\
+        Keyword,Fail,Fail,BuiltIn,Keyword,Fail,Fail,BuiltIn\n\
+        Branch,,Pass,,Branch,,Pass,\n\
+        Branch,,Fail,,Branch,,Fail,\n\
+        Keyword,Fail,Fail,BuiltIn,Keyword,Fail,Fail,BuiltIn\n\
+        Teardown,Log,Pass,BuiltIn,Teardown,Log,Pass,BuiltIn\n";
+    assert_eq!(diff.dump_to_csv_str()?, expect);
+    Ok(())
+}
+
+#[test]
+fn library_api() -> anyhow::Result<()> {
+    let files = vec![
+        ResultFile::from_path("robot/fixtures/rf5_output.xml")?.with_name("rf5"),
+        ResultFile::from_reader("rf6", std::fs::File::open("robot/fixtures/rf6_output.xml")?)?,
+    ];
+    let mrl = Blender::new()
+        .filter(Filter::Level {
+            level: Level::Test,
+            keyword_depth: None,
+        })
+        .run(&files)?;
+    let csv = String::from_utf8(CsvExporter.export_to_vec(&mrl)?)?;
+    let expect = "Type 0,Name 0,Result 0,Owner 0,Type 1,Name 1,Result 1,Owner 1\n\
+        File,rf5,None,,File,rf6,None,\n\
+        Suite,Control,Fail,,Suite,Control,Fail,\n\
+        Test,Loops,Pass,,Test,Loops,Pass,\n\
+        Test,Errors,Fail,,Test,Errors,Fail,\n";
+    assert_eq!(csv, expect);
+    Ok(())
+}
+
+/// Output with many tests calling the same few hundred keywords
+fn large_output(tests: usize, keywords: usize) -> String {
+    let status = r#"<status status="PASS" start="2024-01-01T10:00:00.000000" elapsed="0.001"/>"#;
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<robot generator="Robot 7.1" generated="2024-01-01T10:00:00.000000" rpa="false" schemaversion="5">
+<suite id="s1" name="Large">"#,
+    );
+    for t in 0..tests {
+        xml.push_str(&format!(r#"<test id="s1-t{t}" name="Test {t}" line="1">"#));
+        for k in 0..keywords {
+            xml.push_str(&format!(
+                r#"<kw name="Wait Until Element Is Visible And Enabled {}" owner="SeleniumLibrary">{status}</kw>"#,
+                (t + k) % 300
+            ));
+        }
+        xml.push_str(&format!("{status}</test>"));
+    }
+    xml.push_str(&format!("{status}</suite></robot>"));
+    xml
+}
+
+fn names(element: &Element, all: &mut Vec<Rc<str>>) {
+    for child in element.children.borrow().iter() {
+        all.push(child.name.clone());
+        names(child, all);
+    }
+}
+
+#[test]
+fn large_blend_shares_names() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("blend_result_large_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let xml = large_output(200, 50);
+    let mut files = Vec::new();
+    for run in 0..3 {
+        let file = dir.join(format!("output_{run}.xml"));
+        std::fs::write(&file, &xml)?;
+        files.push(file.to_string_lossy().to_string());
+    }
+
+    let trees = parse_files(&files)?;
+    let mrl = blend_trees(&trees, &files, 0, false)?;
+    // File and suite rows, then every test with its keywords
+    assert_eq!(mrl.list.borrow().len(), 2 + 200 * (1 + 50));
+
+    // Every distinct name is stored once for all runs
+    let mut all = Vec::new();
+    for tree in trees.iter() {
+        names(tree, &mut all);
+    }
+    let mut distinct = HashMap::new();
+    for name in all.iter() {
+        distinct.insert(Rc::as_ptr(name) as *const u8, name.len());
+    }
+    assert_eq!(distinct.len(), 1 + 200 + 249);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::process::Command;
+
+const FIXTURES: [&str; 2] = [
+    "robot/fixtures/rf6_output.xml",
+    "robot/fixtures/rf7_output.xml",
+];
+
+fn stdout_of(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_blend_result"))
+        .args(args)
+        .output()?;
+    assert!(output.status.success(), "{:?}", output);
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn trend_to_stdout() -> anyhow::Result<()> {
+    let csv = stdout_of(&[&["trend", "--format", "csv"], &FIXTURES[..]].concat())?;
+    assert!(csv.starts_with("Test,Status,Failure rate,Last failure,Median duration\n"));
+    let html = stdout_of(&[&["trend", "--format", "html"], &FIXTURES[..]].concat())?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    Ok(())
+}
+
+#[test]
+fn blend_with_positional_depth() -> anyhow::Result<()> {
+    let csv = stdout_of(&[&["blend", "1", "-"], &FIXTURES[..]].concat())?;
+    assert_eq!(
+        csv,
+        stdout_of(&[&["blend", "-d", "1", "-"], &FIXTURES[..]].concat())?
+    );
+    assert!(csv.starts_with("Type 0,"));
+    Ok(())
+}
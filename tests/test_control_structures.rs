@@ -0,0 +1,175 @@
+use anyhow::Context;
+use blend_result::element::{ElementType, ResultType};
+use std::fs;
+mod common;
+
+fn parse_fixture(filename: &str) -> anyhow::Result<String> {
+    common::init_logger();
+    let xml = fs::read_to_string(filename).context(format!("File not found {}", filename))?;
+    blend_result::parse_from_str_to_str(&xml).context("Parsing failed")
+}
+
+#[test]
+fn test_control_structures_rf5() -> anyhow::Result<()> {
+    let results = parse_fixture("robot/fixtures/rf5_output.xml")?;
+    let expect = "Type,Name,Result\n\
+        Robot,,None\n\
+        Suite,Control,Fail\n\
+        Setup,Log,Pass\n\
+        Test,Loops,Pass\n\
+        Setup,No Operation,Pass\n\
+        For,,Pass\n\
+        Iter,,Pass\n\
+        Keyword,Log,Pass\n\
+        Iter,,Pass\n\
+        Continue,,Pass\n\
+        While,,Pass\n\
+        Iter,,Pass\n\
+        Break,,Pass\n\
+        Keyword,Get Answer,Pass\n\
+        Return,,Pass\n\
+        Teardown,Log,Pass\n\
+        Test,Errors,Fail\n\
+        Try,,Fail\n\
+        Branch,,Fail\n\
+        Keyword,Fail,Fail\n\
+        Branch,,Pass\n\
+        Keyword,Log,Pass\n\
+        Branch,,Fail\n\
+        Keyword,Fail,Fail\n\
+        Teardown,Log,Pass\n";
+
+    assert_eq!(results, expect);
+    Ok(())
+}
+
+#[test]
+fn test_control_structures_rf6() -> anyhow::Result<()> {
+    // RF 6.1 adds FOR IN ENUMERATE start, WHILE on_limit, embedded arguments
+    // with sourcename, EXCEPT pattern_type and IF inside of loops
+    let results = parse_fixture("robot/fixtures/rf6_output.xml")?;
+    let expect = "Type,Name,Result\n\
+        Robot,,None\n\
+        Suite,Control,Fail\n\
+        Setup,Log,Pass\n\
+        Test,Loops,Pass\n\
+        Setup,No Operation,Pass\n\
+        For,,Pass\n\
+        Iter,,Pass\n\
+        If,,Pass\n\
+        Branch,,NotRun\n\
+        Continue,,NotRun\n\
+        Keyword,Log,Pass\n\
+        Iter,,Pass\n\
+        If,,Pass\n\
+        Branch,,Pass\n\
+        Continue,,Pass\n\
+        Keyword,Log,NotRun\n\
+        While,,Pass\n\
+        Iter,,Pass\n\
+        Keyword,No Operation,Pass\n\
+        Iter,,Pass\n\
+        Keyword,No Operation,Pass\n\
+        Iter,,Pass\n\
+        Keyword,No Operation,Pass\n\
+        Keyword,Answer Is 42,Pass\n\
+        Return,,Pass\n\
+        Teardown,Log,Pass\n\
+        Test,Errors,Fail\n\
+        Try,,Fail\n\
+        Branch,,Fail\n\
+        Keyword,Fail,Fail\n\
+        Branch,,Pass\n\
+        Keyword,Log,Pass\n\
+        Branch,,Fail\n\
+        Keyword,Fail,Fail\n\
+        Teardown,Log,Pass\n";
+
+    assert_eq!(results, expect);
+
+    let xml = fs::read_to_string("robot/fixtures/rf6_output.xml")?;
+    let root = blend_result::parse_tree(&xml)?;
+    let summary = root.summary.as_ref().context("No summary")?;
+    assert_eq!(summary.metadata.rf_version(), Some("6.1.1"));
+    assert_eq!(summary.errors[0].timestamp, "2023-10-17T10:00:00.006");
+    Ok(())
+}
+
+#[test]
+fn test_unknown_elements_are_errors() {
+    let xml = fs::read_to_string("robot/fixtures/rf6_output.xml").unwrap();
+    let unknown = xml.replace("<timeout value=\"1 minute\"/>", "<unknown/>");
+    let error = blend_result::parse_tree(&unknown).unwrap_err();
+    assert!(error.to_string().contains("Unknown element <unknown/>"));
+    let weird = xml.replace("status=\"NOT RUN\"", "status=\"WEIRD\"");
+    let error = blend_result::parse_tree(&weird).unwrap_err();
+    assert!(error.to_string().contains("Unknown status \"WEIRD\""));
+}
+
+#[test]
+fn test_control_structures_rf7() -> anyhow::Result<()> {
+    let results = parse_fixture("robot/fixtures/rf7_output.xml")?;
+    let expect = "Type,Name,Result\n\
+        Robot,,None\n\
+        Suite,Control,Fail\n\
+        Setup,Log,Pass\n\
+        Test,Loops,Pass\n\
+        Setup,No Operation,Pass\n\
+        For,,Pass\n\
+        Iter,,Pass\n\
+        If,,Pass\n\
+        Branch,,NotRun\n\
+        Continue,,NotRun\n\
+        Iter,,Pass\n\
+        If,,Pass\n\
+        Branch,,Pass\n\
+        Continue,,Pass\n\
+        While,,Pass\n\
+        Iter,,Pass\n\
+        Break,,Pass\n\
+        Keyword,Get Answer,Pass\n\
+        Return,,Pass\n\
+        Teardown,Log,Pass\n\
+        Test,Errors,Fail\n\
+        Var,${x},Pass\n\
+        Group,Prepare,Pass\n\
+        Keyword,No Operation,Pass\n\
+        Try,,Pass\n\
+        Branch,,Fail\n\
+        Keyword,Fail,Fail\n\
+        Branch,,Pass\n\
+        Keyword,Log,Pass\n\
+        Branch,,Pass\n\
+        Keyword,Log,Pass\n\
+        Error,,Fail\n\
+        Teardown,Log,Pass\n";
+
+    assert_eq!(results, expect);
+    Ok(())
+}
+
+#[test]
+fn test_control_structures_rf7_details() -> anyhow::Result<()> {
+    let xml = fs::read_to_string("robot/fixtures/rf7_output.xml")?;
+    let root = blend_result::parse_tree(&xml)?;
+    let suite = root.children.borrow()[0].clone();
+    let test = suite.children.borrow()[2].clone();
+    assert_eq!(&*test.name, "Errors");
+    assert_eq!(test.message, "ELSE does not accept arguments.");
+    assert_eq!(test.elapsed, Some(0.004));
+
+    let error = test.children.borrow()[3].clone();
+    assert_eq!(error.et, ElementType::Error);
+    assert_eq!(error.result, ResultType::Fail);
+    assert_eq!(error.message, "ELSE does not accept arguments.");
+
+    let summary = root.summary.as_ref().context("No summary")?;
+    assert_eq!(summary.total[0].name, "All Tests");
+    assert_eq!((summary.total[0].pass, summary.total[0].fail), (1, 1));
+    assert_eq!(summary.tags[0].name, "loop");
+    assert_eq!(summary.suites[0].name, "Control");
+    assert_eq!(summary.errors.len(), 2);
+    assert_eq!(summary.errors[0].level, "WARN");
+    assert_eq!(summary.errors[1].timestamp, "2026-10-17T10:00:00.000600");
+    Ok(())
+}
@@ -30,7 +30,9 @@ pub fn ods_test(loops: u32) {
     }
     wb.push_sheet(sheet);
 
-    spreadsheet_ods::write_ods(&mut wb, "bench.ods").expect("bench.ods");
+    let path = std::env::temp_dir().join(format!("bench_{}.ods", std::process::id()));
+    spreadsheet_ods::write_ods(&mut wb, &path).expect("bench.ods");
+    std::fs::remove_file(&path).expect("bench.ods");
 }
 
 // cargo test ods_export_time -- --show-output
@@ -20,44 +20,65 @@ fn test_parser_a() -> anyhow::Result<()> {
             ElementFlat {
                 et: ElementType::Robot,
                 result: ResultType::None,
-                name: String::from(""),
+                name: String::from("").into(),
                 depth: 0,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Suite,
                 result: ResultType::Pass,
-                name: String::from("Test A"),
+                name: String::from("Test A").into(),
                 depth: 1,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Test,
                 result: ResultType::Pass,
-                name: String::from("Demo Test A"),
+                name: String::from("Demo Test A").into(),
                 depth: 2,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Keyword,
                 result: ResultType::Pass,
-                name: String::from("No Operation"),
+                name: String::from("No Operation").into(),
                 depth: 3,
+                owner: "BuiltIn".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Test,
                 result: ResultType::Pass,
-                name: String::from("Demo Test B"),
+                name: String::from("Demo Test B").into(),
                 depth: 2,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Keyword,
                 result: ResultType::Pass,
-                name: String::from("Keyword B"),
+                name: String::from("Keyword B").into(),
                 depth: 3,
+                owner: "".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
             ElementFlat {
                 et: ElementType::Keyword,
                 result: ResultType::Pass,
-                name: String::from("No Operation"),
+                name: String::from("No Operation").into(),
                 depth: 4,
+                owner: "BuiltIn".into(),
+                failure: String::new(),
+                issue: String::new(),
             },
         ])),
     };
@@ -140,7 +161,7 @@ fn test_parse_from_str_to_str_c() -> anyhow::Result<()> {
         Keyword,No Operation,Pass\n\
         Keyword,Log,Pass\n\
         Keyword,Fail,Fail\n\
-        Branch,,None\n\
+        Branch,,NotRun\n\
         Keyword,Log,NotRun\n\
         Test,Demo Test D,Pass\n\
         Keyword,Log To Console,Pass\n";